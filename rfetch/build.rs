@@ -0,0 +1,21 @@
+//! Records the git commit rfetch is built from, when there is one, as
+//! `RFETCH_GIT_HASH` for the version field. Tarball builds go without.
+
+use std::process::Command;
+
+fn main() {
+    let git = |args: &[&str]| {
+        let output = Command::new("git").args(args).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    if let Some(hash) = git(&["rev-parse", "--short", "HEAD"]) {
+        println!("cargo:rustc-env=RFETCH_GIT_HASH={}", hash);
+    }
+    // Rebuild when HEAD moves; a branch's HEAD names its ref file.
+    if let Some(dir) = git(&["rev-parse", "--git-dir"]) {
+        println!("cargo:rerun-if-changed={}/HEAD", dir);
+        if let Some(head) = git(&["symbolic-ref", "-q", "HEAD"]) {
+            println!("cargo:rerun-if-changed={}/{}", dir, head);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! A small on-disk cache for slow fields, kept in
+//! `$XDG_CACHE_HOME/rfetch` (or `~/.cache/rfetch`), one file per key.
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("rfetch"))
+}
+
+/// Returns the cached value for `key` if it is younger than `ttl`,
+/// otherwise runs `compute` and caches what it returns. Failures (`None`)
+/// are not cached, so the next run tries again.
+pub fn cached(key: &str, ttl: Duration, compute: impl FnOnce() -> Option<String>) -> Option<String> {
+    let path = cache_dir().map(|dir| dir.join(key));
+
+    if let Some(path) = &path
+        && let Ok(modified) = fs::metadata(path).and_then(|m| m.modified())
+        && SystemTime::now().duration_since(modified).is_ok_and(|age| age < ttl)
+        && let Ok(value) = fs::read_to_string(path)
+    {
+        return Some(value);
+    }
+
+    let value = compute()?;
+    if let Some(path) = &path
+        && path.parent().is_some_and(|dir| fs::create_dir_all(dir).is_ok())
+    {
+        let _ = fs::write(path, &value);
+    }
+    Some(value)
+}
+
+/// True the first time it is called for `key` in this cache, false after.
+/// Without a usable cache directory every call counts as the first.
+pub fn once(key: &str) -> bool {
+    let Some(path) = cache_dir().map(|dir| dir.join(key)) else {
+        return true;
+    };
+    if path.exists() {
+        return false;
+    }
+    if path.parent().is_some_and(|dir| fs::create_dir_all(dir).is_ok()) {
+        let _ = fs::write(&path, "");
+    }
+    true
+}
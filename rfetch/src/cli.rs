@@ -0,0 +1,195 @@
+//! Command-line argument parsing.
+
+use std::path::PathBuf;
+
+use crate::{color::ColorMode, completions::Shell, fields, units::Units};
+
+pub const USAGE: &str = "\
+usage: rfetch [options]
+
+options:
+  --config <path>   config file to use instead of ~/.config/rfetch/config.toml
+  --profile <name>  apply the config's [profile.<name>] on top of the rest
+  --format <name>   output format: human (default), json, prometheus,
+                    html, flat (key=value lines), summary
+  --field-separator <sep>
+                    join flat keys with <sep> instead of `.`
+  --field <name>    show this field; repeat to pick several, shown in the
+                    order given (replaces the config's `fields`)
+  --serve <addr>    serve the fetch as JSON over HTTP on <addr>, e.g.
+                    127.0.0.1:8080 (no auth or TLS: trusted networks only)
+  --output <path>   write the output to <path> instead of stdout; colors
+                    are off unless --color=always
+  --summary         print one line for a status bar, e.g.
+                    `6.2G/15.5G RAM | 40.0% / | up 3h 12m` (same as
+                    --format summary; see [summary] in the config)
+  --fast            only os, kernel, uptime and memory, read straight from
+                    /proc and /etc: no subprocesses, for shell prompts
+  --quiet           print nothing on stdout; only the exit status counts
+  --strict          exit with status 1 when a field's collector fails
+  --watch           redraw the fetch in place until interrupted
+  --refresh <secs>  seconds between --watch frames (default 2, minimum 1)
+  --units <name>    size units: binary (GiB, default) or decimal (GB)
+  --color <when>    auto (default), always or never
+  --anonymize       hide the user, host name, Wi-Fi network name, gateway
+                    and DNS servers
+  -v, --verbose     report recoverable problems, like unknown [labels] keys
+  -h, --help        show this help";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Human,
+    Json,
+    Prometheus,
+    Html,
+    Flat,
+    Summary,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "human" => Ok(Format::Human),
+            "json" => Ok(Format::Json),
+            "prometheus" => Ok(Format::Prometheus),
+            "html" => Ok(Format::Html),
+            "flat" => Ok(Format::Flat),
+            "summary" => Ok(Format::Summary),
+            _ => Err(format!("unknown format `{}`", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Args {
+    pub config: Option<PathBuf>,
+    /// `--profile`: a `[profile.<name>]` table from the config.
+    pub profile: Option<String>,
+    pub format: Option<Format>,
+    /// `--field-separator`: overrides `[flat] separator`.
+    pub field_separator: Option<String>,
+    /// Overrides the config file's `units` when given.
+    pub units: Option<Units>,
+    pub color: Option<ColorMode>,
+    /// `--field` names in command-line order. Empty means use the config.
+    pub fields: Vec<String>,
+    /// File for `--output`; stdout when unset.
+    pub output: Option<PathBuf>,
+    /// Address for `--serve`.
+    pub serve: Option<String>,
+    /// `--fast`: only the fields in [`fields::FAST_FIELDS`].
+    pub fast: bool,
+    pub quiet: bool,
+    pub strict: bool,
+    pub watch: bool,
+    /// Seconds between `--watch` frames.
+    pub refresh: Option<u64>,
+    pub anonymize: bool,
+    pub verbose: bool,
+    pub help: bool,
+    /// `rfetch completions <shell>`, left out of the usage text.
+    pub completions: Option<Shell>,
+    /// `rfetch schema`, also unlisted.
+    pub schema: bool,
+    /// `rfetch sensors`: list temperature sensors for `temp_sensor`.
+    pub sensors: bool,
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args {
+            config: None,
+            profile: None,
+            format: None,
+            field_separator: None,
+            units: None,
+            color: None,
+            fields: Vec::new(),
+            output: None,
+            serve: None,
+            fast: false,
+            quiet: false,
+            strict: false,
+            watch: false,
+            refresh: None,
+            anonymize: false,
+            verbose: false,
+            help: false,
+            completions: None,
+            schema: false,
+            sensors: false,
+        };
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`.
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("`{}` needs a value", name))
+            };
+
+            match flag.as_str() {
+                "--config" => parsed.config = Some(PathBuf::from(value("--config")?)),
+                "--profile" => parsed.profile = Some(value("--profile")?),
+                "--format" => parsed.format = Some(Format::parse(&value("--format")?)?),
+                "--field-separator" => parsed.field_separator = Some(value("--field-separator")?),
+                "--units" => parsed.units = Some(Units::parse(&value("--units")?)?),
+                "--color" => parsed.color = Some(ColorMode::parse(&value("--color")?)?),
+                "--field" => {
+                    let name = value("--field")?;
+                    // `custom:` and `file:` names are checked against the config later.
+                    let configured = [fields::CUSTOM_PREFIX, fields::FILE_PREFIX].iter().any(|p| name.starts_with(p));
+                    if fields::find(&name).is_none() && !configured {
+                        return Err(format!("unknown field `{}`", name));
+                    }
+                    parsed.fields.push(name);
+                }
+                "--output" => parsed.output = Some(PathBuf::from(value("--output")?)),
+                "--serve" => parsed.serve = Some(value("--serve")?),
+                "--fast" => parsed.fast = true,
+                "--summary" => parsed.format = Some(Format::Summary),
+                "--quiet" => parsed.quiet = true,
+                "--strict" => parsed.strict = true,
+                "--watch" => parsed.watch = true,
+                "--refresh" => {
+                    let raw = value("--refresh")?;
+                    // Redrawing faster than this just burns CPU re-reading /proc.
+                    match raw.parse::<u64>() {
+                        Ok(secs) if secs >= 1 => parsed.refresh = Some(secs),
+                        _ => return Err(format!("`--refresh` must be a whole number of seconds, at least 1 (got `{}`)", raw)),
+                    }
+                }
+                "--anonymize" => parsed.anonymize = true,
+                "-v" | "--verbose" => parsed.verbose = true,
+                "-h" | "--help" => parsed.help = true,
+                "completions" => {
+                    let shell = args.next().ok_or("`completions` needs a shell: bash, zsh or fish")?;
+                    parsed.completions = Some(Shell::parse(&shell)?);
+                }
+                "schema" => parsed.schema = true,
+                "sensors" => parsed.sensors = true,
+                _ => return Err(format!("unknown option `{}`", arg)),
+            }
+        }
+
+        if parsed.watch && parsed.serve.is_some() {
+            return Err("`--watch` and `--serve` can't be combined".to_string());
+        }
+        if parsed.output.is_some() && (parsed.watch || parsed.serve.is_some()) {
+            return Err("`--output` can't be combined with `--watch` or `--serve`".to_string());
+        }
+        if parsed.quiet && (parsed.watch || parsed.serve.is_some() || parsed.output.is_some()) {
+            return Err("`--quiet` can't be combined with `--watch`, `--serve` or `--output`".to_string());
+        }
+        if parsed.refresh.is_some() && !parsed.watch {
+            return Err("`--refresh` only applies to `--watch`".to_string());
+        }
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,347 @@
+//! ANSI colors, and deciding whether to emit them at all.
+
+use std::io::IsTerminal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// Color when stdout is a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn parse(name: &str) -> Result<ColorMode, String> {
+        match name {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(format!("unknown color mode `{}` (expected auto, always or never)", name)),
+        }
+    }
+
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// Whether the terminal advertises 24-bit color through `COLORTERM`.
+/// Everything else gets the nearest xterm-256 color instead.
+pub fn truecolor() -> bool {
+    matches!(std::env::var("COLORTERM").as_deref(), Ok("truecolor" | "24bit"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// Pick `Dark` or `Light` from the terminal's background color.
+    Auto,
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub fn parse(name: &str) -> Result<Theme, String> {
+        match name {
+            "auto" => Ok(Theme::Auto),
+            "dark" => Ok(Theme::Dark),
+            "light" => Ok(Theme::Light),
+            _ => Err(format!("unknown theme `{}` (expected auto, dark or light)", name)),
+        }
+    }
+
+    /// Resolves `Auto` by asking the terminal for its background, falling
+    /// back to `Dark` when it doesn't answer.
+    pub fn detect(self) -> Theme {
+        match self {
+            Theme::Auto => match terminal_background() {
+                Some(background) if background.luminance() > 0.5 => Theme::Light,
+                _ => Theme::Dark,
+            },
+            theme => theme,
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        let (title, label, warning) = match self {
+            Theme::Light => ("1;34", "34", RED),
+            Theme::Auto | Theme::Dark => ("1;96", "96", YELLOW),
+        };
+        Palette {
+            title: title.to_string(),
+            label: label.to_string(),
+            warning: warning.to_string(),
+            separator: String::new(),
+            bar: [GREEN, YELLOW, RED].map(str::to_string),
+        }
+    }
+}
+
+/// SGR codes for each part of the output.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    pub title: String,
+    pub label: String,
+    pub warning: String,
+    /// Empty to leave the separator line uncolored.
+    pub separator: String,
+    /// Bar fill below `bar.warn`, from there up to `bar.critical`, and above.
+    pub bar: [String; 3],
+}
+
+impl Palette {
+    /// Maps a base16 scheme onto the output the way base16 templates for
+    /// editors do: headings (`base0D`) for the title, support (`base0C`)
+    /// for labels, comments (`base03`) for the separator, and the green,
+    /// yellow and red accents for bars and warnings. Values keep the
+    /// terminal's foreground, which a base16 terminal theme sets to `base05`.
+    pub fn from_scheme(scheme: &Scheme, truecolor: bool) -> Palette {
+        let code = |base: usize| scheme[base].code(truecolor);
+        Palette {
+            title: format!("1;{}", code(0x0d)),
+            label: code(0x0c),
+            warning: code(0x0a),
+            separator: code(0x03),
+            bar: [code(0x0b), code(0x0a), code(0x08)],
+        }
+    }
+}
+
+/// A base16 scheme's colors, `base00` to `base0F`.
+pub type Scheme = [Rgb; 16];
+
+/// Reads the `base00` to `base0F` entries of a base16 or base24 YAML
+/// scheme, either flat (`base00: "282828"`) or nested under the newer
+/// format's `palette:` (`base00: "#282828"`). Other keys, base24's
+/// `base10` to `base17` among them, are skipped.
+pub fn parse_scheme(text: &str) -> Result<Scheme, String> {
+    let mut colors: [Option<Rgb>; 16] = [None; 16];
+    for line in text.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let Some(index) = key.trim().strip_prefix("base").and_then(|n| u8::from_str_radix(n, 16).ok()) else {
+            continue;
+        };
+        if key.trim().len() != 6 || index >= 16 {
+            continue;
+        }
+        let value = value.trim();
+        let value = value.split_once(" #").map_or(value, |(value, _)| value).trim().trim_matches(['"', '\'']);
+        let hex = value.strip_prefix('#').unwrap_or(value);
+        colors[index as usize] = Some(Rgb::parse(&format!("#{}", hex)).map_err(|e| format!("{}: {}", key.trim(), e))?);
+    }
+    let mut scheme = [Rgb(0, 0, 0); 16];
+    for (i, color) in colors.into_iter().enumerate() {
+        scheme[i] = color.ok_or_else(|| format!("no `base{:02X}` color", i))?;
+    }
+    Ok(scheme)
+}
+
+pub const GREEN: &str = "32";
+pub const YELLOW: &str = "33";
+pub const RED: &str = "31";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Parses `#rrggbb` or the short `#rgb`.
+    pub fn parse(hex: &str) -> Result<Rgb, String> {
+        let invalid = || format!("invalid color `{}` (expected #rrggbb)", hex);
+        let digits = hex.strip_prefix('#').ok_or_else(invalid)?;
+        if !digits.is_ascii() {
+            return Err(invalid());
+        }
+        let channel = |s: &str| u8::from_str_radix(s, 16).map_err(|_| invalid());
+        match digits.len() {
+            6 => Ok(Rgb(channel(&digits[0..2])?, channel(&digits[2..4])?, channel(&digits[4..6])?)),
+            3 => {
+                let short = |i: usize| channel(&digits[i..=i]).map(|v| v * 17);
+                Ok(Rgb(short(0)?, short(1)?, short(2)?))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// The foreground SGR code for this color.
+    fn code(self, truecolor: bool) -> String {
+        let Rgb(r, g, b) = self;
+        if truecolor {
+            return format!("38;2;{};{};{}", r, g, b);
+        }
+        // Nearest point of the 6x6x6 cube, whose levels are 0, 95, 135, ...
+        let level = |c: u8| match c {
+            0..48 => 0,
+            48..115 => 1,
+            _ => (c as u16 - 35) / 40,
+        };
+        format!("38;5;{}", 16 + 36 * level(r) + 6 * level(g) + level(b))
+    }
+
+    /// The color xterm shows for 256-color index `n`: its 16 standard
+    /// colors, then the 6x6x6 cube, then 24 grays.
+    pub fn ansi256(n: u8) -> Rgb {
+        const STANDARD: [Rgb; 16] = [
+            Rgb(0x00, 0x00, 0x00), Rgb(0xcd, 0x00, 0x00), Rgb(0x00, 0xcd, 0x00), Rgb(0xcd, 0xcd, 0x00),
+            Rgb(0x00, 0x00, 0xee), Rgb(0xcd, 0x00, 0xcd), Rgb(0x00, 0xcd, 0xcd), Rgb(0xe5, 0xe5, 0xe5),
+            Rgb(0x7f, 0x7f, 0x7f), Rgb(0xff, 0x00, 0x00), Rgb(0x00, 0xff, 0x00), Rgb(0xff, 0xff, 0x00),
+            Rgb(0x5c, 0x5c, 0xff), Rgb(0xff, 0x00, 0xff), Rgb(0x00, 0xff, 0xff), Rgb(0xff, 0xff, 0xff),
+        ];
+        let level = |i: u8| if i == 0 { 0 } else { 55 + 40 * i };
+        match n {
+            0..16 => STANDARD[n as usize],
+            16..232 => {
+                let i = n - 16;
+                Rgb(level(i / 36), level(i / 6 % 6), level(i % 6))
+            }
+            _ => {
+                let gray = 8 + 10 * (n - 232);
+                Rgb(gray, gray, gray)
+            }
+        }
+    }
+
+    /// `#rrggbb`, as CSS takes it.
+    pub fn hex(self) -> String {
+        let Rgb(r, g, b) = self;
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    /// Relative luminance, 0.0 (black) to 1.0 (white).
+    fn luminance(self) -> f64 {
+        let Rgb(r, g, b) = self;
+        (0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64) / 255.0
+    }
+
+    fn mix(self, other: Rgb, t: f64) -> Rgb {
+        let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb(lerp(self.0, other.0), lerp(self.1, other.1), lerp(self.2, other.2))
+    }
+}
+
+/// Colors each character of `text` along a gradient from `from` to `to`.
+pub fn gradient(text: &str, from: Rgb, to: Rgb, truecolor: bool) -> String {
+    let count = text.chars().count();
+    let mut out = String::new();
+    for (i, c) in text.chars().enumerate() {
+        let t = if count > 1 { i as f64 / (count - 1) as f64 } else { 0.0 };
+        out.push_str(&format!("\x1b[{}m{}", from.mix(to, t).code(truecolor), c));
+    }
+    if count > 0 {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+/// Wraps `text` in the SGR sequence `code` when `enabled`.
+pub fn paint(text: &str, code: &str, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Asks the terminal for its background color with an OSC 11 query. The
+/// tty is put in raw mode for the exchange and restored afterwards; a
+/// terminal that stays quiet for 100ms counts as no answer.
+fn terminal_background() -> Option<Rgb> {
+    use std::{fs::OpenOptions, io::{Read, Write}, os::fd::AsRawFd};
+
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    let fd = tty.as_raw_fd();
+
+    let mut saved: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut saved) } != 0 {
+        return None;
+    }
+    let mut raw = saved;
+    raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+    raw.c_cc[libc::VMIN] = 0;
+    raw.c_cc[libc::VTIME] = 0;
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+        return None;
+    }
+
+    let mut reply = Vec::new();
+    if tty.write_all(b"\x1b]11;?\x07").and_then(|_| tty.flush()).is_ok() {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(100);
+        // Replies end in BEL or ST (`ESC \`).
+        while !reply.ends_with(b"\x07") && !reply.ends_with(b"\x1b\\") {
+            let left = deadline.saturating_duration_since(std::time::Instant::now());
+            let mut poll = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+            if left.is_zero() || unsafe { libc::poll(&mut poll, 1, left.as_millis() as libc::c_int) } <= 0 {
+                break;
+            }
+            let mut buf = [0u8; 64];
+            match tty.read(&mut buf) {
+                Ok(n) if n > 0 => reply.extend_from_slice(&buf[..n]),
+                _ => break,
+            }
+        }
+    }
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &saved) };
+
+    parse_osc11(&String::from_utf8_lossy(&reply))
+}
+
+/// Parses `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` (1 to 4 hex digits per channel).
+fn parse_osc11(reply: &str) -> Option<Rgb> {
+    let spec = reply.split_once("rgb:")?.1;
+    let spec = spec.trim_end_matches(['\x07', '\\']).trim_end_matches('\x1b');
+    let mut channels = spec.split('/').map(|hex| {
+        if hex.is_empty() || hex.len() > 4 {
+            return None;
+        }
+        let max = 16u32.pow(hex.len() as u32) - 1;
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        Some((value * 255 / max) as u8)
+    });
+    Some(Rgb(channels.next()??, channels.next()??, channels.next()??))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_parse() {
+        assert_eq!(Rgb::parse("#ff8000"), Ok(Rgb(255, 128, 0)));
+        assert_eq!(Rgb::parse("#f80"), Ok(Rgb(255, 136, 0)));
+        assert!(Rgb::parse("ff8000").is_err());
+        assert!(Rgb::parse("#ff80").is_err());
+    }
+
+    #[test]
+    fn osc11_replies() {
+        assert_eq!(parse_osc11("\x1b]11;rgb:ffff/ffff/ffff\x07"), Some(Rgb(255, 255, 255)));
+        assert_eq!(parse_osc11("\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\"), Some(Rgb(30, 30, 46)));
+        assert_eq!(parse_osc11("\x1b]11;rgb:f/8/0\x07"), Some(Rgb(255, 136, 0)));
+        assert_eq!(parse_osc11("\x1b]11;rgb:ffff/ffff\x07"), None);
+        assert_eq!(parse_osc11("garbage"), None);
+    }
+
+    #[test]
+    fn base16_schemes() {
+        let flat: String = (0..16).map(|i| format!("base{:02X}: \"{:02x}0000\" # base{:02X}\n", i, i * 16, i)).collect();
+        let scheme = parse_scheme(&format!("scheme: \"Test\"\nauthor: \"me\"\n{}", flat)).unwrap();
+        assert_eq!(scheme[0x0d], Rgb(0xd0, 0, 0));
+        let nested = "system: \"base24\"\npalette:\n".to_string()
+            + &(0..24).map(|i| format!("  base{:02X}: \"#00{:02x}00\"\n", i, i)).collect::<String>();
+        assert_eq!(parse_scheme(&nested).unwrap()[0x0f], Rgb(0, 0x0f, 0));
+        assert_eq!(parse_scheme("base00: \"282828\"").unwrap_err(), "no `base01` color");
+        let palette = Palette::from_scheme(&scheme, false);
+        assert_eq!(palette.title, format!("1;{}", Rgb(0xd0, 0, 0).code(false)));
+    }
+}
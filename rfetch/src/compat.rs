@@ -0,0 +1,101 @@
+//! Running as a `neofetch` or `fastfetch` drop-in. When rfetch is started
+//! through a symlink with one of those names, the flags scripts commonly
+//! pass are translated to rfetch's own or dropped when they don't apply,
+//! so the scripts keep working.
+
+use crate::cache;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Neofetch,
+    Fastfetch,
+}
+
+impl Tool {
+    /// The tool `argv0` (e.g. `/usr/local/bin/neofetch`) names, if any.
+    pub fn from_argv0(argv0: &str) -> Option<Tool> {
+        match argv0.rsplit('/').next()? {
+            "neofetch" => Some(Tool::Neofetch),
+            "fastfetch" => Some(Tool::Fastfetch),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Neofetch => "neofetch",
+            Tool::Fastfetch => "fastfetch",
+        }
+    }
+}
+
+/// What to do with a foreign flag.
+enum Rule {
+    /// Drop it, and the value after it if `takes_value`.
+    Ignore { takes_value: bool, why: &'static str },
+    /// Replace it with these rfetch arguments.
+    Replace(&'static [&'static str]),
+    /// A config path: kept for `.toml` files, which can only be rfetch's,
+    /// and dropped otherwise.
+    Config,
+}
+
+fn rule(tool: Tool, flag: &str) -> Option<Rule> {
+    let ignore = |takes_value, why| Some(Rule::Ignore { takes_value, why });
+    match (tool, flag) {
+        (Tool::Neofetch, "--off") => ignore(false, "rfetch has no logo"),
+        (Tool::Neofetch, "--ascii_distro" | "--backend" | "--source") => ignore(true, "rfetch has no logo"),
+        (Tool::Neofetch, "--color_blocks") => ignore(true, "rfetch has no color blocks"),
+        (Tool::Neofetch, "--config") => Some(Rule::Config),
+        (Tool::Neofetch, "--stdout") => Some(Rule::Replace(&["--color", "never"])),
+        (Tool::Fastfetch, "-l" | "--logo") => ignore(true, "rfetch has no logo"),
+        (Tool::Fastfetch, "-c" | "--config" | "--load-config") => Some(Rule::Config),
+        (Tool::Fastfetch, "--pipe") => Some(Rule::Replace(&["--color", "never"])),
+        _ => None,
+    }
+}
+
+/// Rewrites `args` for rfetch's parser. The first time anything is
+/// translated, a note on stderr names the native equivalents.
+pub fn translate(tool: Tool, args: Vec<String>) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut notes = Vec::new();
+    let mut args = args.into_iter().peekable();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        match rule(tool, &flag) {
+            None => out.push(arg),
+            Some(Rule::Ignore { takes_value, why }) => {
+                if takes_value && inline.is_none() {
+                    args.next();
+                }
+                notes.push(format!("ignored `{}` ({})", flag, why));
+            }
+            Some(Rule::Replace(native)) => {
+                // fastfetch's boolean flags may carry an explicit `true`/`false`.
+                let value = inline.or_else(|| args.next_if(|next| next == "true" || next == "false"));
+                if value.as_deref() != Some("false") {
+                    out.extend(native.iter().map(|a| a.to_string()));
+                }
+                notes.push(format!("`{}` is `{}` in rfetch", flag, native.join(" ")));
+            }
+            Some(Rule::Config) => match inline.or_else(|| args.next()) {
+                Some(path) if path.ends_with(".toml") => out.extend(["--config".to_string(), path]),
+                _ => notes.push(format!(
+                    "ignored `{}` ({} configs don't apply; set RFETCH_CONFIG to an rfetch TOML file)",
+                    flag,
+                    tool.name()
+                )),
+            },
+        }
+    }
+
+    if !notes.is_empty() && cache::once("compat-note") {
+        eprintln!("rfetch: running as {}: {}", tool.name(), notes.join("; "));
+    }
+    out
+}
@@ -0,0 +1,162 @@
+//! `rfetch completions <shell>`: completion scripts for bash, zsh and fish,
+//! printed to stdout. Field names come from [`fields::FIELDS`], so new
+//! fields complete without touching this file.
+
+use std::fmt::Write;
+
+use crate::fields;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn parse(name: &str) -> Result<Shell, String> {
+        match name {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(format!("unknown shell `{}` (expected bash, zsh or fish)", name)),
+        }
+    }
+}
+
+/// What an option's value completes to.
+enum Value {
+    None,
+    File,
+    Free,
+    Words(Vec<&'static str>),
+}
+
+struct Opt {
+    long: &'static str,
+    short: Option<char>,
+    help: &'static str,
+    value: Value,
+    repeatable: bool,
+}
+
+fn options() -> Vec<Opt> {
+    let opt = |long, help, value| Opt { long, short: None, help, value, repeatable: false };
+    vec![
+        opt("config", "config file to use", Value::File),
+        opt("profile", "config profile to apply", Value::Free),
+        opt("format", "output format", Value::Words(vec!["human", "json", "prometheus", "html", "flat", "summary"])),
+        opt("field-separator", "join flat keys with this", Value::Free),
+        Opt {
+            repeatable: true,
+            ..opt("field", "show this field", Value::Words(fields::FIELDS.iter().map(|f| f.name).collect()))
+        },
+        opt("output", "write the output to a file", Value::File),
+        opt("serve", "serve JSON over HTTP on an address", Value::Free),
+        opt("summary", "one line for a status bar", Value::None),
+        opt("fast", "only the cheapest fields", Value::None),
+        opt("quiet", "print nothing, only set the exit status", Value::None),
+        opt("strict", "exit 1 when a collector fails", Value::None),
+        opt("watch", "redraw in place until interrupted", Value::None),
+        opt("refresh", "seconds between --watch frames", Value::Free),
+        opt("units", "size units", Value::Words(vec!["binary", "decimal"])),
+        opt("color", "when to use color", Value::Words(vec!["auto", "always", "never"])),
+        opt("anonymize", "hide identifying details", Value::None),
+        Opt { short: Some('v'), ..opt("verbose", "report recoverable problems", Value::None) },
+        Opt { short: Some('h'), ..opt("help", "show help", Value::None) },
+    ]
+}
+
+pub fn generate(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash(),
+        Shell::Zsh => zsh(),
+        Shell::Fish => fish(),
+    }
+}
+
+fn bash() -> String {
+    let options = options();
+    let mut out = String::new();
+    let _ = writeln!(out, "_rfetch() {{");
+    let _ = writeln!(out, "    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"");
+    let _ = writeln!(out, "    case \"$prev\" in");
+    for opt in &options {
+        match &opt.value {
+            Value::None => {}
+            Value::File => {
+                let _ = writeln!(out, "        --{}) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;", opt.long);
+            }
+            Value::Free => {
+                let _ = writeln!(out, "        --{}) return ;;", opt.long);
+            }
+            Value::Words(words) => {
+                let _ = writeln!(
+                    out,
+                    "        --{}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;",
+                    opt.long,
+                    words.join(" ")
+                );
+            }
+        }
+    }
+    let _ = writeln!(out, "    esac");
+    let flags: Vec<String> = options
+        .iter()
+        .flat_map(|opt| opt.short.map(|c| format!("-{}", c)).into_iter().chain([format!("--{}", opt.long)]))
+        .collect();
+    let _ = writeln!(out, "    COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", flags.join(" "));
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out, "complete -F _rfetch rfetch");
+    out
+}
+
+fn zsh() -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "#compdef rfetch");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "_arguments \\");
+    for opt in options() {
+        let value = match &opt.value {
+            Value::None => String::new(),
+            Value::File => format!(":{}:_files", opt.long),
+            Value::Free => format!(":{}: ", opt.long),
+            Value::Words(words) => format!(":{}:({})", opt.long, words.join(" ")),
+        };
+        let repeat = if opt.repeatable { "*" } else { "" };
+        let spec = match opt.short {
+            Some(c) => format!("'(-{0} --{1})'{{-{0},--{1}}}'[{2}]{3}'", c, opt.long, opt.help, value),
+            None => format!("'{}--{}[{}]{}'", repeat, opt.long, opt.help, value),
+        };
+        let _ = writeln!(out, "  {} \\", spec);
+    }
+    // Drop the continuation after the last spec.
+    out.truncate(out.len() - " \\\n".len());
+    out.push('\n');
+    out
+}
+
+fn fish() -> String {
+    let mut out = String::new();
+    for opt in options() {
+        let _ = write!(out, "complete -c rfetch");
+        if let Some(c) = opt.short {
+            let _ = write!(out, " -s {}", c);
+        }
+        let _ = write!(out, " -l {}", opt.long);
+        match &opt.value {
+            Value::None => {}
+            Value::File => {
+                let _ = write!(out, " -r -F");
+            }
+            Value::Free => {
+                let _ = write!(out, " -x");
+            }
+            Value::Words(words) => {
+                let _ = write!(out, " -x -a \"{}\"", words.join(" "));
+            }
+        }
+        let _ = writeln!(out, " -d \"{}\"", opt.help);
+    }
+    out
+}
@@ -0,0 +1,1147 @@
+//! User configuration, read from `$XDG_CONFIG_HOME/rfetch/config.toml`
+//! (falling back to `~/.config/rfetch/config.toml`). Every option has a
+//! default, so a missing file is the same as an empty one.
+//!
+//! Options that can also be set elsewhere resolve in this order, first
+//! match wins:
+//!
+//! 1. command-line flags (`--config`, `--profile`, `--color`, `--field`,
+//!    `--format`, `--units`);
+//! 2. environment variables (`RFETCH_CONFIG`, `RFETCH_PROFILE`,
+//!    `RFETCH_NO_COLOR`, `RFETCH_FORMAT`);
+//! 3. the config file;
+//! 4. built-in defaults.
+//!
+//! See [`Config::resolve`].
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cli::{Args, Format},
+    color::{self, ColorMode, Palette, Rgb, Scheme, Theme},
+    fields, summary,
+    toml::{self, Table, Value},
+    units::{self, Unit, Units},
+};
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// `format`: output format, `human` unless set.
+    pub format: Format,
+    /// `fields`: which fields to show, in order. Defaults to every field
+    /// not marked opt-in in [`fields::FIELDS`].
+    pub fields: Vec<String>,
+    /// `color`: `"auto"` (the default), `"always"` or `"never"`.
+    pub color: ColorMode,
+    /// `theme`: `"dark"` (the default), `"light"`, or `"auto"` to follow
+    /// the terminal background.
+    pub theme: Theme,
+    /// `scheme`: a base16 or base24 scheme file whose colors replace the
+    /// theme's.
+    pub scheme: Option<Scheme>,
+    /// `[title] format`: template for the first line. Supports `{user}`,
+    /// `{host}` and `{os}`; an empty string hides the line.
+    pub title_format: String,
+    /// `[title] separator`: whether to print the dashed line under the title.
+    pub separator: bool,
+    /// `[title] gradient`: two `#rrggbb` colors to fade the title between.
+    pub title_gradient: Option<(Rgb, Rgb)>,
+    /// `[title] lowercase`: lowercase the rendered title.
+    pub title_lowercase: bool,
+    /// `label_case`: casing applied to every label, after `[labels]`.
+    pub label_case: LabelCase,
+    /// `align`: where the whole block sits in the terminal.
+    pub align: Align,
+    /// `units`: `"binary"` (GiB, the default) or `"decimal"` (GB) for every
+    /// memory, swap and storage size.
+    pub units: Units,
+    /// `precision`: decimals (0 to 3) in every size and percentage.
+    /// Defaults to 1.
+    pub precision: usize,
+    /// `hostname`: `"short"` (the default) drops everything from the first
+    /// dot of a fully qualified name; `"full"` keeps it.
+    pub hostname: HostnameStyle,
+    /// `gpu`: which GPUs to list. `None` picks `discrete` on laptops and
+    /// `all` elsewhere.
+    pub gpu: Option<GpuMode>,
+    /// `memory_unit`: a unit like `"MiB"` the memory field always uses.
+    /// `None` (`"auto"`, the default) picks one from the total.
+    pub memory_unit: Option<Unit>,
+    /// `memory_percent`: add the share of memory in use, e.g. `(93.8%)`.
+    pub memory_percent: bool,
+    /// `uptime_style`: how the uptime line reads. Defaults to `elapsed`.
+    pub uptime_style: UptimeStyle,
+    /// `uptime_idle`: add how idle the CPUs have been since boot to the
+    /// elapsed uptime.
+    pub uptime_idle: bool,
+    /// `kernel_build`: add the kernel's compile date to the kernel line.
+    pub kernel_build: bool,
+    /// `kernel_highlight`: color the local-version suffix of the kernel
+    /// release, e.g. the `-mine+` of `6.9.0-mine+`.
+    pub kernel_highlight: bool,
+    /// `swap_detail`: list each swap device under the swap total.
+    pub swap_detail: bool,
+    /// `modules_top`: how many of the largest kernel modules to list under
+    /// the modules count.
+    pub modules_top: usize,
+    /// `updates_ttl`: seconds to reuse the cached update count.
+    pub updates_ttl: u64,
+    /// `git_dirty`: whether the git field runs `git status` to mark
+    /// uncommitted changes.
+    pub git_dirty: bool,
+    /// `storage`: which mounts the storage field lists.
+    pub storage: StorageMounts,
+    /// `storage_network`: whether `storage = "auto"` also lists NFS, SMB
+    /// and sshfs mounts. Off by default, since a dead server can hang them.
+    pub storage_network: bool,
+    /// `storage_min_size`: with `storage = "auto"`, hide mounts smaller
+    /// than this many bytes (written as e.g. `"1GiB"`). Listed paths are
+    /// always shown.
+    pub storage_min_size: u64,
+    /// `storage_inodes`: add each mount's inode usage, for filesystems that
+    /// can run out of inodes before space.
+    pub storage_inodes: bool,
+    /// `storage_overlay_upper`: for an overlay mount, show the space of the
+    /// writable upper layer rather than the merged view.
+    pub storage_overlay_upper: bool,
+    /// `storage_sort`: the order storage mounts are shown in.
+    pub storage_sort: StorageSort,
+    /// `storage_max`: show at most this many mounts, then a count of the
+    /// rest and a total over all of them. 0, the default, shows every one.
+    pub storage_max: usize,
+    /// `temp_sensor`: the `chip/label` the cpu_temp field reads, e.g.
+    /// `k10temp/Tctl`. Unset picks one automatically.
+    pub temp_sensor: Option<String>,
+    /// `cmdline_filter`: prefixes of the kernel parameters the cmdline
+    /// field shows. An empty list shows the whole command line.
+    pub cmdline_filter: Vec<String>,
+    /// `[packages]`: which package sources the packages field counts.
+    pub packages: PackageSources,
+    /// `[flat]`: how `--format flat` writes its lines.
+    pub flat: FlatConfig,
+    /// `[summary]`: the one-line `--summary` output.
+    pub summary: SummaryConfig,
+    /// `[bar]`: usage bars after memory, swap and storage.
+    pub bar: BarConfig,
+    /// `[[custom]]`: fields filled in by shell commands, listed in
+    /// `fields` as `custom:<name>`.
+    pub custom: Vec<CustomField>,
+    /// `[[file]]`: fields showing the first line of a file, listed in
+    /// `fields` as `file:<name>`.
+    pub files: Vec<FileField>,
+    /// `[labels]`: field name to the label shown instead of the default,
+    /// e.g. `memory = "RAM"`.
+    pub labels: BTreeMap<String, String>,
+    /// Whether loading reported an error and carried on without the broken
+    /// part, which makes rfetch exit with status 2. For the config file the
+    /// broken part is the whole file.
+    pub errored: bool,
+    /// Set by `--anonymize`: redact the user, host name, SSID, gateway and
+    /// DNS servers.
+    pub anonymize: bool,
+    /// Set by `--fast`: collect and show only [`fields::FAST_FIELDS`].
+    pub fast: bool,
+    /// Set by `--verbose` rather than the file: report problems that
+    /// don't stop the fetch.
+    pub verbose: bool,
+}
+
+/// One `[[custom]]` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomField {
+    pub name: String,
+    /// Defaults to the name.
+    pub label: String,
+    /// Run with `sh -c`; its stdout, one value per line, is the field.
+    pub command: String,
+}
+
+/// One `[[file]]` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileField {
+    /// Defaults to the label.
+    pub name: String,
+    /// Defaults to the name.
+    pub label: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageSources {
+    /// `native`: the distro's package manager (pacman, dpkg, apk or rpm).
+    pub native: bool,
+    /// `flatpak`: runs `flatpak list`.
+    pub flatpak: bool,
+    /// `snap`: snaps installed through snapd.
+    pub snap: bool,
+    /// `aur`: foreign pacman packages, from `pacman -Qm`.
+    pub aur: bool,
+}
+
+impl Default for PackageSources {
+    fn default() -> Self {
+        PackageSources { native: true, flatpak: true, snap: true, aur: true }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageMounts {
+    /// The mounts backing these paths, in order. Defaults to just `/`.
+    Paths(Vec<String>),
+    /// Every mounted disk.
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageSort {
+    /// As listed in `storage`, or as the system enumerates disks for `auto`.
+    #[default]
+    None,
+    /// Largest total first. Mounts without a size go last.
+    Size,
+    /// By mount path.
+    Path,
+}
+
+impl StorageSort {
+    fn parse(name: &str) -> Result<StorageSort, String> {
+        match name {
+            "none" => Ok(StorageSort::None),
+            "size" => Ok(StorageSort::Size),
+            "path" => Ok(StorageSort::Path),
+            _ => Err(format!("unknown storage sort `{}` (expected size, path or none)", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlatConfig {
+    /// `separator`: joins the keys of a path, `.` by default.
+    pub separator: String,
+    /// `assign`: between a path and its value, `=` by default.
+    pub assign: String,
+}
+
+impl Default for FlatConfig {
+    fn default() -> Self {
+        FlatConfig { separator: ".".into(), assign: "=".into() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SummaryConfig {
+    /// `items`: templates over field names and the summary's own tokens;
+    /// an item with a token that has no value is left out.
+    pub items: Vec<String>,
+    /// `delimiter`: between items, ` | ` by default.
+    pub delimiter: String,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        SummaryConfig {
+            items: ["{mem_used}/{mem_total} RAM", "{disk_percent} {disk_mount}", "up {uptime}"].map(String::from).to_vec(),
+            delimiter: " | ".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BarConfig {
+    /// `enabled`: draw bars at all. Off by default.
+    pub enabled: bool,
+    /// `width`: bar length in cells.
+    pub width: usize,
+    /// `scale`: size storage bars relative to the largest mount shown,
+    /// instead of all at `width`.
+    pub scale: bool,
+    /// `min_width`: shortest scaled bar, so tiny mounts stay visible.
+    pub min_width: usize,
+    /// `color`: paint the filled part green, yellow or red by how full it
+    /// is, when colors are on.
+    pub color: bool,
+    /// `warn` and `critical`: fill percentages where the bar turns yellow
+    /// and red.
+    pub warn: u8,
+    pub critical: u8,
+}
+
+impl Default for BarConfig {
+    fn default() -> Self {
+        BarConfig {
+            enabled: false,
+            width: 10,
+            scale: false,
+            min_width: 3,
+            color: true,
+            warn: 70,
+            critical: 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    /// Pushed against the terminal's right edge, lines still starting in
+    /// one column.
+    Right,
+}
+
+impl Align {
+    fn parse(name: &str) -> Result<Align, String> {
+        match name {
+            "left" => Ok(Align::Left),
+            "right" => Ok(Align::Right),
+            _ => Err(format!("unknown align `{}` (expected left or right)", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LabelCase {
+    /// As defined or overridden.
+    #[default]
+    AsIs,
+    Upper,
+    Lower,
+    /// First letter of each word upper, the rest lower: `Term Font`.
+    Title,
+}
+
+impl LabelCase {
+    fn parse(name: &str) -> Result<LabelCase, String> {
+        match name {
+            "as-is" => Ok(LabelCase::AsIs),
+            "upper" => Ok(LabelCase::Upper),
+            "lower" => Ok(LabelCase::Lower),
+            "title" => Ok(LabelCase::Title),
+            _ => Err(format!("unknown label case `{}` (expected as-is, upper, lower or title)", name)),
+        }
+    }
+
+    pub fn apply(self, label: &str) -> String {
+        match self {
+            LabelCase::AsIs => label.to_string(),
+            LabelCase::Upper => label.to_uppercase(),
+            LabelCase::Lower => label.to_lowercase(),
+            LabelCase::Title => {
+                let mut out = String::with_capacity(label.len());
+                let mut start = true;
+                for c in label.chars() {
+                    if start {
+                        out.extend(c.to_uppercase());
+                    } else {
+                        out.extend(c.to_lowercase());
+                    }
+                    start = c.is_whitespace();
+                }
+                out
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UptimeStyle {
+    /// Time since boot: `3h 12m`.
+    #[default]
+    Elapsed,
+    /// The boot time itself: `since 2026-10-14 09:12`.
+    Absolute,
+    /// The boot time, humanized: `3 weeks ago`.
+    Relative,
+}
+
+impl UptimeStyle {
+    fn parse(name: &str) -> Result<UptimeStyle, String> {
+        match name {
+            "elapsed" => Ok(UptimeStyle::Elapsed),
+            "absolute" => Ok(UptimeStyle::Absolute),
+            "relative" => Ok(UptimeStyle::Relative),
+            _ => Err(format!("unknown uptime style `{}` (expected elapsed, absolute or relative)", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HostnameStyle {
+    /// `box` for `box.lan.example.com`. IP addresses stay whole.
+    #[default]
+    Short,
+    /// Whatever the system reports.
+    Full,
+}
+
+impl HostnameStyle {
+    fn parse(name: &str) -> Result<HostnameStyle, String> {
+        match name {
+            "short" => Ok(HostnameStyle::Short),
+            "full" => Ok(HostnameStyle::Full),
+            _ => Err(format!("unknown hostname style `{}` (expected short or full)", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuMode {
+    /// Every display controller, boot GPU first.
+    All,
+    /// Only the GPU currently rendering (`DRI_PRIME` or the boot display).
+    Primary,
+    /// Only discrete cards, or everything when there are none.
+    Discrete,
+}
+
+impl GpuMode {
+    fn parse(name: &str) -> Result<GpuMode, String> {
+        match name {
+            "all" => Ok(GpuMode::All),
+            "primary" => Ok(GpuMode::Primary),
+            "discrete" => Ok(GpuMode::Discrete),
+            _ => Err(format!("unknown gpu mode `{}` (expected all, primary or discrete)", name)),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            format: Format::Human,
+            fields: fields::default_fields(),
+            color: ColorMode::default(),
+            theme: Theme::default(),
+            scheme: None,
+            title_format: "{user}@{host}".into(),
+            separator: true,
+            title_gradient: None,
+            title_lowercase: false,
+            label_case: LabelCase::default(),
+            align: Align::default(),
+            units: Units::default(),
+            precision: 1,
+            hostname: HostnameStyle::default(),
+            gpu: None,
+            memory_unit: None,
+            memory_percent: false,
+            uptime_style: UptimeStyle::default(),
+            uptime_idle: false,
+            kernel_build: false,
+            kernel_highlight: false,
+            swap_detail: false,
+            modules_top: 0,
+            updates_ttl: 3600,
+            git_dirty: true,
+            storage: StorageMounts::Paths(vec!["/".into()]),
+            storage_network: false,
+            storage_min_size: 0,
+            storage_inodes: false,
+            storage_overlay_upper: false,
+            storage_sort: StorageSort::default(),
+            storage_max: 0,
+            temp_sensor: None,
+            cmdline_filter: [
+                "root=", "init=", "resume=", "mitigations=", "iommu=", "intel_iommu=", "amd_iommu=",
+                "nomodeset", "quiet", "splash",
+            ]
+            .map(String::from)
+            .to_vec(),
+            packages: PackageSources::default(),
+            flat: FlatConfig::default(),
+            summary: SummaryConfig::default(),
+            bar: BarConfig::default(),
+            custom: Vec::new(),
+            files: Vec::new(),
+            labels: BTreeMap::new(),
+            errored: false,
+            anonymize: false,
+            fast: false,
+            verbose: false,
+        }
+    }
+}
+
+impl Config {
+    /// The effective configuration for this run: the config file (from
+    /// `--config`, `$RFETCH_CONFIG` or the default location), then
+    /// environment overrides, then command-line overrides.
+    pub fn resolve(args: &Args) -> Config {
+        Config::resolve_with(args, |name| std::env::var(name).ok())
+    }
+
+    /// [`Config::resolve`], reading environment variables through `var`.
+    fn resolve_with(args: &Args, var: impl Fn(&str) -> Option<String>) -> Config {
+        let env = |name: &str| var(name).filter(|v| !v.is_empty());
+
+        let explicit = args.config.clone().or_else(|| env("RFETCH_CONFIG").map(PathBuf::from));
+        let profile = args.profile.clone().or_else(|| env("RFETCH_PROFILE"));
+        let mut config = Config::load(explicit, profile.as_deref());
+
+        if env("RFETCH_NO_COLOR").is_some() {
+            config.color = ColorMode::Never;
+        }
+        if let Some(format) = env("RFETCH_FORMAT") {
+            match Format::parse(&format) {
+                Ok(format) => config.format = format,
+                Err(e) => {
+                    eprintln!("rfetch: RFETCH_FORMAT: {}", e);
+                    config.errored = true;
+                }
+            }
+        }
+
+        if let Some(format) = args.format {
+            config.format = format;
+        }
+        if let Some(color) = args.color {
+            config.color = color;
+        } else if args.output.is_some() && config.color == ColorMode::Auto {
+            // A file is no terminal, whatever stdout is.
+            config.color = ColorMode::Never;
+        }
+        if let Some(units) = args.units {
+            config.units = units;
+        }
+        if let Some(separator) = &args.field_separator {
+            if separator.is_empty() {
+                eprintln!("rfetch: `--field-separator` must not be empty");
+                config.errored = true;
+            } else {
+                config.flat.separator = separator.clone();
+            }
+        }
+        if !args.fields.is_empty() {
+            config.fields = Vec::new();
+            for name in &args.fields {
+                match config.check_field(name) {
+                    Ok(()) => config.fields.push(name.clone()),
+                    Err(e) => {
+                        eprintln!("rfetch: --field: {}", e);
+                        config.errored = true;
+                    }
+                }
+            }
+        }
+
+        if config.format == Format::Summary {
+            // The line shows its items only, so only they are collected.
+            config.fields = summary::fields(&config);
+        }
+
+        config.anonymize = args.anonymize;
+        config.fast = args.fast;
+        if config.fast {
+            config.fields.retain(|name| fields::FAST_FIELDS.contains(&name.as_str()));
+        }
+        config.verbose = args.verbose;
+        if config.verbose {
+            for name in config.labels.keys().filter(|name| fields::find(name).is_none()) {
+                eprintln!("rfetch: [labels]: unknown field `{}`", name);
+            }
+        }
+        config
+    }
+
+    /// The colors to paint with: the scheme's when one is loaded, else
+    /// the theme's.
+    pub fn palette(&self) -> Palette {
+        match &self.scheme {
+            Some(scheme) => Palette::from_scheme(scheme, color::truecolor()),
+            None => self.theme.palette(),
+        }
+    }
+
+    /// The `[[custom]]` block a `custom:<name>` field refers to.
+    pub fn custom_field(&self, field: &str) -> Option<&CustomField> {
+        let name = field.strip_prefix(fields::CUSTOM_PREFIX)?;
+        self.custom.iter().find(|custom| custom.name == name)
+    }
+
+    /// The `[[file]]` block a `file:<name>` field refers to.
+    pub fn file_field(&self, field: &str) -> Option<&FileField> {
+        let name = field.strip_prefix(fields::FILE_PREFIX)?;
+        self.files.iter().find(|file| file.name == name)
+    }
+
+    /// Whether `name` is a built-in field, or a `custom:` or `file:` one
+    /// with a block.
+    pub fn check_field(&self, name: &str) -> Result<(), String> {
+        if let Some(custom) = name.strip_prefix(fields::CUSTOM_PREFIX) {
+            return match self.custom_field(name) {
+                Some(_) => Ok(()),
+                None => Err(format!("no `[[custom]]` block named `{}` for `{}`", custom, name)),
+            };
+        }
+        if let Some(file) = name.strip_prefix(fields::FILE_PREFIX) {
+            return match self.file_field(name) {
+                Some(_) => Ok(()),
+                None => Err(format!("no `[[file]]` block named `{}` for `{}`", file, name)),
+            };
+        }
+        match fields::find(name) {
+            Some(_) => Ok(()),
+            None => Err(format!("unknown field `{}`", name)),
+        }
+    }
+
+    /// Loads the config file, falling back to defaults when it is missing.
+    /// A broken file is reported on stderr rather than aborting the fetch;
+    /// so is an `explicit` path that doesn't exist, or an unknown `profile`.
+    fn load(explicit: Option<PathBuf>, profile: Option<&str>) -> Config {
+        let path = match explicit {
+            Some(path) => path,
+            None => match config_path() {
+                Some(path) if path.exists() => path,
+                _ => {
+                    let mut config = Config::default();
+                    if let Some(name) = profile {
+                        eprintln!("rfetch: unknown profile `{}` (there is no config file)", name);
+                        config.errored = true;
+                    }
+                    return config;
+                }
+            },
+        };
+        let table = load_table(&path, &mut Vec::new()).and_then(|table| with_profile(table, profile));
+        match table.and_then(|table| Config::from_table(&table)) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("rfetch: {}", e);
+                Config { errored: true, ..Config::default() }
+            }
+        }
+    }
+
+    fn from_table(table: &Table) -> Result<Config, String> {
+        let mut config = Config::default();
+
+        if let Some(title) = get_table(table, "title")? {
+            if let Some(format) = get_str(title, "title.format")? {
+                config.title_format = format;
+            }
+            if let Some(separator) = get_bool(title, "title.separator")? {
+                config.separator = separator;
+            }
+            if let Some(colors) = get_str_array(title, "title.gradient")? {
+                let [from, to] = colors.as_slice() else {
+                    return Err("`title.gradient` must list exactly two colors".to_string());
+                };
+                config.title_gradient = Some((Rgb::parse(from)?, Rgb::parse(to)?));
+            }
+            if let Some(lowercase) = get_bool(title, "title.lowercase")? {
+                config.title_lowercase = lowercase;
+            }
+        }
+
+        if let Some(flat) = get_table(table, "flat")? {
+            for (key, target) in [("flat.separator", &mut config.flat.separator), ("flat.assign", &mut config.flat.assign)] {
+                if let Some(value) = get_str(flat, key)? {
+                    if value.is_empty() {
+                        return Err(format!("`{}` must not be empty", key));
+                    }
+                    *target = value;
+                }
+            }
+        }
+        if let Some(summary) = get_table(table, "summary")? {
+            if let Some(items) = get_str_array(summary, "summary.items")? {
+                config.summary.items = items;
+            }
+            if let Some(delimiter) = get_str(summary, "summary.delimiter")? {
+                config.summary.delimiter = delimiter;
+            }
+        }
+        if let Some(bar) = get_table(table, "bar")? {
+            if let Some(enabled) = get_bool(bar, "bar.enabled")? {
+                config.bar.enabled = enabled;
+            }
+            if let Some(width) = get_uint(bar, "bar.width")? {
+                config.bar.width = width as usize;
+            }
+            if let Some(scale) = get_bool(bar, "bar.scale")? {
+                config.bar.scale = scale;
+            }
+            if let Some(min_width) = get_uint(bar, "bar.min_width")? {
+                config.bar.min_width = min_width as usize;
+            }
+            if let Some(color) = get_bool(bar, "bar.color")? {
+                config.bar.color = color;
+            }
+            for (key, threshold) in [("bar.warn", &mut config.bar.warn), ("bar.critical", &mut config.bar.critical)] {
+                if let Some(percent) = get_uint(bar, key)? {
+                    if percent > 100 {
+                        return Err(format!("`{}` must be a percentage, at most 100, found {}", key, percent));
+                    }
+                    *threshold = percent as u8;
+                }
+            }
+            if config.bar.warn > config.bar.critical {
+                return Err("`bar.warn` can't be above `bar.critical`".to_string());
+            }
+        }
+
+        if let Some(packages) = get_table(table, "packages")? {
+            let toggles = [
+                ("packages.native", &mut config.packages.native),
+                ("packages.flatpak", &mut config.packages.flatpak),
+                ("packages.snap", &mut config.packages.snap),
+                ("packages.aur", &mut config.packages.aur),
+            ];
+            for (key, toggle) in toggles {
+                if let Some(enabled) = get_bool(packages, key)? {
+                    *toggle = enabled;
+                }
+            }
+        }
+
+        if let Some(labels) = get_table(table, "labels")? {
+            for (name, label) in labels {
+                match label {
+                    Value::String(label) => {
+                        config.labels.insert(name.clone(), label.clone());
+                    }
+                    other => return Err(type_error(&format!("labels.{}", name), "string", other)),
+                }
+            }
+        }
+
+        if let Some(format) = get_str(table, "format")? {
+            config.format = Format::parse(&format)?;
+        }
+        for block in get_blocks(table, "custom")? {
+            let name = get_str(block, "custom.name")?.ok_or("every `[[custom]]` block needs a `name`")?;
+            let command = get_str(block, "custom.command")?
+                .ok_or_else(|| format!("`[[custom]]` `{}` needs a `command`", name))?;
+            if config.custom.iter().any(|custom| custom.name == name) {
+                return Err(format!("`[[custom]]` `{}` is defined twice", name));
+            }
+            let label = get_str(block, "custom.label")?.unwrap_or_else(|| name.clone());
+            config.custom.push(CustomField { name, label, command });
+        }
+        for block in get_blocks(table, "file")? {
+            let (name, label) = match (get_str(block, "file.name")?, get_str(block, "file.label")?) {
+                (Some(name), Some(label)) => (name, label),
+                (Some(one), None) | (None, Some(one)) => (one.clone(), one),
+                (None, None) => return Err("every `[[file]]` block needs a `name` or `label`".to_string()),
+            };
+            let path = get_str(block, "file.path")?.ok_or_else(|| format!("`[[file]]` `{}` needs a `path`", name))?;
+            if config.files.iter().any(|file| file.name == name) {
+                return Err(format!("`[[file]]` `{}` is defined twice", name));
+            }
+            config.files.push(FileField { name, label, path: PathBuf::from(path) });
+        }
+        if let Some(list) = get_str_array(table, "fields")? {
+            for name in &list {
+                config.check_field(name).map_err(|e| format!("{} in `fields`", e))?;
+            }
+            config.fields = list;
+        } else {
+            // Without a `fields` list, custom and file fields follow the
+            // defaults.
+            let custom = config.custom.iter().map(|custom| format!("{}{}", fields::CUSTOM_PREFIX, custom.name));
+            let files = config.files.iter().map(|file| format!("{}{}", fields::FILE_PREFIX, file.name));
+            config.fields.extend(custom.chain(files));
+        }
+        if let Some(color) = get_str(table, "color")? {
+            config.color = ColorMode::parse(&color)?;
+        }
+        if let Some(precision) = get_uint(table, "precision")? {
+            if precision > 3 {
+                return Err(format!("`precision` must be between 0 and 3, found {}", precision));
+            }
+            config.precision = precision as usize;
+        }
+        if let Some(theme) = get_str(table, "theme")? {
+            config.theme = Theme::parse(&theme)?;
+        }
+        if let Some(path) = get_str(table, "scheme")? {
+            let path = expand_home(&path);
+            let text = fs::read_to_string(&path).map_err(|e| format!("scheme {}: {}", path.display(), e))?;
+            let scheme = color::parse_scheme(&text).map_err(|e| format!("scheme {}: {}", path.display(), e))?;
+            config.scheme = Some(scheme);
+        }
+        if let Some(units) = get_str(table, "units")? {
+            config.units = Units::parse(&units)?;
+        }
+        if let Some(style) = get_str(table, "hostname")? {
+            config.hostname = HostnameStyle::parse(&style)?;
+        }
+        if let Some(gpu) = get_str(table, "gpu")? {
+            config.gpu = Some(GpuMode::parse(&gpu)?);
+        }
+        if let Some(unit) = get_str(table, "memory_unit")? {
+            config.memory_unit = match unit.as_str() {
+                "auto" => None,
+                unit => Some(Unit::parse(unit)?),
+            };
+        }
+        if let Some(percent) = get_bool(table, "memory_percent")? {
+            config.memory_percent = percent;
+        }
+        if let Some(style) = get_str(table, "uptime_style")? {
+            config.uptime_style = UptimeStyle::parse(&style)?;
+        }
+        if let Some(idle) = get_bool(table, "uptime_idle")? {
+            config.uptime_idle = idle;
+        }
+        if let Some(build) = get_bool(table, "kernel_build")? {
+            config.kernel_build = build;
+        }
+        if let Some(highlight) = get_bool(table, "kernel_highlight")? {
+            config.kernel_highlight = highlight;
+        }
+        if let Some(detail) = get_bool(table, "swap_detail")? {
+            config.swap_detail = detail;
+        }
+        if let Some(top) = get_uint(table, "modules_top")? {
+            config.modules_top = top as usize;
+        }
+        if let Some(ttl) = get_uint(table, "updates_ttl")? {
+            config.updates_ttl = ttl;
+        }
+        if let Some(dirty) = get_bool(table, "git_dirty")? {
+            config.git_dirty = dirty;
+        }
+        match lookup(table, "storage") {
+            None => {}
+            Some(Value::String(s)) if s == "auto" => config.storage = StorageMounts::Auto,
+            Some(Value::String(s)) => {
+                return Err(format!("unknown storage mode `{}` (expected \"auto\" or a list of paths)", s));
+            }
+            Some(_) => {
+                let paths = get_str_array(table, "storage")?.unwrap_or_default();
+                config.storage = StorageMounts::Paths(paths);
+            }
+        }
+        if let Some(network) = get_bool(table, "storage_network")? {
+            config.storage_network = network;
+        }
+        if let Some(inodes) = get_bool(table, "storage_inodes")? {
+            config.storage_inodes = inodes;
+        }
+        if let Some(upper) = get_bool(table, "storage_overlay_upper")? {
+            config.storage_overlay_upper = upper;
+        }
+        if let Some(size) = get_str(table, "storage_min_size")? {
+            config.storage_min_size = units::parse_size(&size)?;
+        }
+        if let Some(case) = get_str(table, "label_case")? {
+            config.label_case = LabelCase::parse(&case)?;
+        }
+        if let Some(align) = get_str(table, "align")? {
+            config.align = Align::parse(&align)?;
+        }
+        if let Some(sort) = get_str(table, "storage_sort")? {
+            config.storage_sort = StorageSort::parse(&sort)?;
+        }
+        if let Some(sensor) = get_str(table, "temp_sensor")? {
+            config.temp_sensor = Some(sensor);
+        }
+        if let Some(max) = get_uint(table, "storage_max")? {
+            config.storage_max = max as usize;
+        }
+        if let Some(filter) = get_str_array(table, "cmdline_filter")? {
+            config.cmdline_filter = filter;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Reads and parses `path`, first merging in the files named by its
+/// `include` key (a string or a list, relative to `path`'s directory).
+/// `stack` holds the files currently being loaded, to catch cycles.
+fn load_table(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Table, String> {
+    let canonical = fs::canonicalize(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if stack.contains(&canonical) {
+        let chain: Vec<String> = stack
+            .iter()
+            .chain([&canonical])
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(format!("include cycle: {}", chain.join(" -> ")));
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut table = toml::parse(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let includes = match table.remove("include") {
+        None => Vec::new(),
+        Some(Value::String(s)) => vec![s],
+        Some(Value::Array(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(s) => Ok(s),
+                other => Err(type_error("include", "list of strings", &other)),
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("{}: {}", path.display(), e))?,
+        Some(other) => {
+            let e = type_error("include", "string or list of strings", &other);
+            return Err(format!("{}: {}", path.display(), e));
+        }
+    };
+
+    stack.push(canonical);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut merged = Table::new();
+    for include in includes {
+        let included = load_table(&dir.join(include), stack)?;
+        merge(&mut merged, included);
+    }
+    stack.pop();
+
+    merge(&mut merged, table);
+    Ok(merged)
+}
+
+/// Merges `over` into `base`: tables merge key by key, anything else in
+/// `over` replaces what `base` had.
+fn merge(base: &mut Table, over: Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(over_table)) => merge(base_table, over_table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Merges the `[profile.<name>]` table over the rest of the config. Without
+/// a `name`, the `default` profile applies if there is one.
+fn with_profile(mut table: Table, name: Option<&str>) -> Result<Table, String> {
+    let profiles = match table.remove("profile") {
+        None => Table::new(),
+        Some(Value::Table(profiles)) => profiles,
+        Some(other) => return Err(type_error("profile", "table", &other)),
+    };
+    let key = name.unwrap_or("default");
+    let overlay = match profiles.get(key) {
+        Some(Value::Table(overlay)) => overlay.clone(),
+        Some(other) => return Err(type_error(&format!("profile.{}", key), "table", other)),
+        None if name.is_none() => return Ok(table),
+        None => {
+            let known: Vec<&str> = profiles.keys().map(String::as_str).collect();
+            return Err(match known.as_slice() {
+                [] => format!("unknown profile `{}` (the config defines none)", key),
+                _ => format!("unknown profile `{}` (expected {})", key, known.join(", ")),
+            });
+        }
+    };
+    merge(&mut table, overlay);
+    Ok(table)
+}
+
+/// `path` with a leading `~/` replaced by `$HOME`.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config` when it isn't set.
+pub fn config_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+fn config_path() -> Option<PathBuf> {
+    Some(config_home()?.join("rfetch").join("config.toml"))
+}
+
+/// Looks up the last segment of the dotted `key` in `table`.
+fn lookup<'t>(table: &'t Table, key: &str) -> Option<&'t Value> {
+    table.get(key.rsplit('.').next().unwrap_or(key))
+}
+
+fn type_error(key: &str, expected: &str, found: &Value) -> String {
+    format!("`{}` must be a {}, found {}", key, expected, found.type_name())
+}
+
+fn get_table<'t>(table: &'t Table, key: &str) -> Result<Option<&'t Table>, String> {
+    match lookup(table, key) {
+        None => Ok(None),
+        Some(Value::Table(t)) => Ok(Some(t)),
+        Some(other) => Err(type_error(key, "table", other)),
+    }
+}
+
+/// The tables of an array of tables like `[[custom]]`.
+fn get_blocks<'t>(table: &'t Table, key: &str) -> Result<Vec<&'t Table>, String> {
+    match lookup(table, key) {
+        None => Ok(Vec::new()),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| match item {
+                Value::Table(block) => Ok(block),
+                other => Err(type_error(key, "list of tables", other)),
+            })
+            .collect(),
+        Some(other) => Err(type_error(key, "list of tables", other)),
+    }
+}
+
+fn get_str(table: &Table, key: &str) -> Result<Option<String>, String> {
+    match lookup(table, key) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(other) => Err(type_error(key, "string", other)),
+    }
+}
+
+fn get_bool(table: &Table, key: &str) -> Result<Option<bool>, String> {
+    match lookup(table, key) {
+        None => Ok(None),
+        Some(Value::Boolean(b)) => Ok(Some(*b)),
+        Some(other) => Err(type_error(key, "boolean", other)),
+    }
+}
+
+fn get_str_array(table: &Table, key: &str) -> Result<Option<Vec<String>>, String> {
+    match lookup(table, key) {
+        None => Ok(None),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(s) => Ok(s.clone()),
+                other => Err(type_error(key, "list of strings", other)),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some),
+        Some(other) => Err(type_error(key, "list of strings", other)),
+    }
+}
+
+fn get_uint(table: &Table, key: &str) -> Result<Option<u64>, String> {
+    match lookup(table, key) {
+        None => Ok(None),
+        Some(Value::Integer(i)) => u64::try_from(*i)
+            .map(Some)
+            .map_err(|_| format!("`{}` must not be negative", key)),
+        Some(other) => Err(type_error(key, "integer", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(text: &str, profile: Option<&str>) -> Result<Config, String> {
+        Config::from_table(&with_profile(toml::parse(text).unwrap(), profile)?)
+    }
+
+    /// Resolves `argv` against a config file holding `file` and an
+    /// environment holding just `env`.
+    fn resolved(name: &str, file: &str, env: &[(&str, &str)], argv: &[&str]) -> Config {
+        let path = std::env::temp_dir().join(format!("rfetch-test-{}-{}.toml", std::process::id(), name));
+        fs::write(&path, file).unwrap();
+        let mut argv: Vec<String> = argv.iter().map(|arg| arg.to_string()).collect();
+        argv.extend(["--config".to_string(), path.display().to_string()]);
+        let args = Args::parse(argv).unwrap();
+        let config = Config::resolve_with(&args, |name| {
+            env.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        });
+        let _ = fs::remove_file(path);
+        config
+    }
+
+    #[test]
+    fn precedence() {
+        // The command line over the environment.
+        let config = resolved("cli-env", "", &[("RFETCH_FORMAT", "flat")], &["--format", "json"]);
+        assert_eq!(config.format, Format::Json);
+        let config = resolved("cli-no-color", "", &[("RFETCH_NO_COLOR", "1")], &["--color", "always"]);
+        assert_eq!(config.color, ColorMode::Always);
+
+        // The environment over the file.
+        let config = resolved("env-file", "format = \"html\"\n", &[("RFETCH_FORMAT", "flat")], &[]);
+        assert_eq!(config.format, Format::Flat);
+        let config = resolved("no-color-file", "color = \"always\"\n", &[("RFETCH_NO_COLOR", "1")], &[]);
+        assert_eq!(config.color, ColorMode::Never);
+        // Set but empty counts as unset.
+        let config = resolved("empty-env", "color = \"always\"\n", &[("RFETCH_NO_COLOR", "")], &[]);
+        assert_eq!(config.color, ColorMode::Always);
+
+        // The file over the defaults.
+        let file = "format = \"html\"\nunits = \"decimal\"\ncolor = \"never\"\nfields = [\"kernel\"]\n";
+        let config = resolved("file", file, &[], &[]);
+        assert_eq!(
+            (config.format, config.units, config.color, config.fields),
+            (Format::Html, Units::Decimal, ColorMode::Never, vec!["kernel".to_string()])
+        );
+        let config = resolved("default", "", &[], &[]);
+        let default = Config::default();
+        assert_eq!(
+            (config.format, config.units, config.color, config.fields),
+            (default.format, default.units, default.color, default.fields)
+        );
+
+        // And the command line over the file.
+        let config = resolved("cli-file", file, &[], &["--units", "binary", "--field", "os"]);
+        assert_eq!((config.units, config.fields), (Units::Binary, vec!["os".to_string()]));
+    }
+
+    #[test]
+    fn errors_drop_what_they_break() {
+        // A config file that fails to load is dropped whole, valid keys
+        // included, while a bad variable costs only itself.
+        let config = resolved("broken-file", "units = \"decimal\"\ncolor = 5\n", &[], &[]);
+        assert!(config.errored);
+        assert_eq!(config.units, Config::default().units);
+        let config = resolved("bad-env", "units = \"decimal\"\n", &[("RFETCH_FORMAT", "yaml")], &[]);
+        assert!(config.errored);
+        assert_eq!((config.units, config.format), (Units::Decimal, Format::Human));
+    }
+
+    #[test]
+    fn profiles_overlay_the_base() {
+        let text = "fields = [\"os\", \"kernel\"]\nunits = \"decimal\"\n\
+                    [title]\nformat = \"{host}\"\n\
+                    [profile.default]\nfields = [\"os\"]\n\
+                    [profile.prompt.title]\nformat = \"\"\n";
+        let base = config(text, None).unwrap();
+        assert_eq!(base.fields, ["os"]);
+        assert_eq!(base.title_format, "{host}");
+
+        let prompt = config(text, Some("prompt")).unwrap();
+        assert_eq!(prompt.fields, ["os", "kernel"]);
+        assert_eq!(prompt.title_format, "");
+        assert_eq!(prompt.units, Units::Decimal);
+
+        let unknown = config(text, Some("login")).unwrap_err();
+        assert_eq!(unknown, "unknown profile `login` (expected default, prompt)");
+    }
+
+    #[test]
+    fn custom_fields_interleave() {
+        let block = "[[custom]]\nname = \"vpn\"\ncommand = \"wg show interfaces\"\n";
+        let listed = config(&format!("fields = [\"os\", \"custom:vpn\", \"kernel\"]\n{}", block), None).unwrap();
+        assert_eq!(listed.fields, ["os", "custom:vpn", "kernel"]);
+        assert_eq!(listed.custom_field("custom:vpn").map(|c| c.label.as_str()), Some("vpn"));
+
+        let appended = config(block, None).unwrap();
+        assert_eq!(appended.fields.last().map(String::as_str), Some("custom:vpn"));
+
+        let missing = config("fields = [\"custom:vpn\"]\n", None).unwrap_err();
+        assert_eq!(missing, "no `[[custom]]` block named `vpn` for `custom:vpn` in `fields`");
+    }
+
+    #[test]
+    fn file_fields_name_defaults_to_label() {
+        let text = "fields = [\"file:Quote\"]\n[[file]]\nlabel = \"Quote\"\npath = \"/etc/motd.quote\"\n";
+        let file = config(text, None).unwrap().file_field("file:Quote").cloned();
+        let expected = FileField { name: "Quote".into(), label: "Quote".into(), path: "/etc/motd.quote".into() };
+        assert_eq!(file, Some(expected));
+
+        let missing = config("[[file]]\nname = \"build\"\n", None).unwrap_err();
+        assert_eq!(missing, "`[[file]]` `build` needs a `path`");
+    }
+}
@@ -0,0 +1,83 @@
+//! The fields rfetch can show, by the name used in the config's `fields`
+//! list.
+
+pub struct FieldDef {
+    pub name: &'static str,
+    pub label: &'static str,
+    /// Shown when the config doesn't list fields explicitly. Opt-in fields
+    /// are usually slow (subprocesses) or niche.
+    pub default: bool,
+}
+
+pub const FIELDS: &[FieldDef] = &[
+    FieldDef { name: "os", label: "OS", default: true },
+    FieldDef { name: "host", label: "Host", default: true },
+    FieldDef { name: "init", label: "Init", default: true },
+    FieldDef { name: "kernel", label: "Kernel", default: true },
+    FieldDef { name: "uptime", label: "Uptime", default: true },
+    FieldDef { name: "shell", label: "Shell", default: true },
+    FieldDef { name: "dm", label: "DM", default: true },
+    FieldDef { name: "terminal", label: "Terminal", default: true },
+    FieldDef { name: "term_font", label: "Term Font", default: true },
+    FieldDef { name: "multiplexer", label: "Multiplexer", default: false },
+    FieldDef { name: "term_size", label: "Terminal Size", default: false },
+    FieldDef { name: "locks", label: "Locks", default: false },
+    FieldDef { name: "gpu", label: "GPU", default: true },
+    FieldDef { name: "memory", label: "Memory", default: true },
+    FieldDef { name: "ram_modules", label: "RAM HW", default: false },
+    FieldDef { name: "swap", label: "Swap", default: true },
+    FieldDef { name: "storage", label: "Storage", default: true },
+    FieldDef { name: "scheduler", label: "Scheduler", default: false },
+    FieldDef { name: "services", label: "Services", default: false },
+    FieldDef { name: "packages", label: "Packages", default: false },
+    FieldDef { name: "updates", label: "Updates", default: false },
+    FieldDef { name: "git", label: "Git", default: false },
+    FieldDef { name: "version", label: "rfetch", default: false },
+    FieldDef { name: "fan", label: "Fan", default: false },
+    FieldDef { name: "thermal", label: "Thermal", default: false },
+    FieldDef { name: "cpu_temp", label: "CPU Temp", default: false },
+    FieldDef { name: "cpu_power", label: "CPU Power", default: false },
+    FieldDef { name: "power", label: "Power", default: false },
+    FieldDef { name: "opengl", label: "OpenGL", default: false },
+    FieldDef { name: "vulkan", label: "Vulkan", default: false },
+    FieldDef { name: "displays", label: "Displays", default: false },
+    FieldDef { name: "monitor", label: "Monitor", default: false },
+    FieldDef { name: "scale", label: "Scale", default: false },
+    FieldDef { name: "microarch", label: "Microarch", default: false },
+    FieldDef { name: "topology", label: "Topology", default: false },
+    FieldDef { name: "governor", label: "Governor", default: false },
+    FieldDef { name: "cmdline", label: "Cmdline", default: false },
+    FieldDef { name: "compositor", label: "Compositor", default: false },
+    FieldDef { name: "notifications", label: "Notifs", default: false },
+    FieldDef { name: "security", label: "Security", default: false },
+    FieldDef { name: "pressure", label: "Pressure", default: false },
+    FieldDef { name: "files", label: "Files", default: false },
+    FieldDef { name: "entropy", label: "Entropy", default: false },
+    FieldDef { name: "modules", label: "Modules", default: false },
+    FieldDef { name: "network", label: "Network", default: false },
+    FieldDef { name: "gateway", label: "Gateway", default: false },
+    FieldDef { name: "dns", label: "DNS", default: false },
+];
+
+/// The fields `--fast` keeps: each is a read of one file under /proc or
+/// /etc, with no subprocess or directory walk.
+pub const FAST_FIELDS: &[&str] = &["os", "kernel", "uptime", "memory"];
+
+/// Prefix of a `fields` entry naming a `[[custom]]` block, as in
+/// `custom:vpn`.
+pub const CUSTOM_PREFIX: &str = "custom:";
+
+/// Prefix of a `fields` entry naming a `[[file]]` block.
+pub const FILE_PREFIX: &str = "file:";
+
+pub fn find(name: &str) -> Option<&'static FieldDef> {
+    FIELDS.iter().find(|f| f.name == name)
+}
+
+pub fn default_fields() -> Vec<String> {
+    FIELDS
+        .iter()
+        .filter(|f| f.default)
+        .map(|f| f.name.to_string())
+        .collect()
+}
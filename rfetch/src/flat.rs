@@ -0,0 +1,85 @@
+//! `--format flat`: the JSON output as one `key=value` line per value, with
+//! object keys and array indices joined into a path, e.g.
+//! `memory.used_bytes=6543210000` or `storage.0.mount=/`. Made for `grep`
+//! and, with `separator = "_"`, for shell `eval`.
+
+use crate::{
+    config::Config,
+    info::SystemInfo,
+    json::{self, Json},
+};
+
+pub fn render(info: &SystemInfo, config: &Config) -> String {
+    let mut out = String::new();
+    flatten(&mut out, "", &json::to_json(info), config);
+    out
+}
+
+/// Writes a line for every scalar under `value`. Empty arrays and objects
+/// have no scalars, so they write nothing; `null` writes an empty value.
+fn flatten(out: &mut String, path: &str, value: &Json, config: &Config) {
+    let child = |key: &str| {
+        if path.is_empty() { key.to_string() } else { format!("{}{}{}", path, config.flat.separator, key) }
+    };
+    let scalar = match value {
+        Json::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(out, &child(&i.to_string()), item, config);
+            }
+            return;
+        }
+        Json::Object(entries) => {
+            for (key, item) in entries {
+                flatten(out, &child(key), item, config);
+            }
+            return;
+        }
+        Json::Null => String::new(),
+        Json::Bool(b) => b.to_string(),
+        Json::Int(n) => n.to_string(),
+        Json::Float(f) if f.is_finite() => f.to_string(),
+        Json::Float(_) => String::new(),
+        Json::Str(s) => quote(s),
+    };
+    out.push_str(path);
+    out.push_str(&config.flat.assign);
+    out.push_str(&scalar);
+    out.push('\n');
+}
+
+/// Leaves plain words alone and single-quotes anything else the way a POSIX
+/// shell reads it back, so values with spaces or quotes survive `eval`.
+fn quote(s: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_-./:@%+,".contains(c);
+    if !s.is_empty() && s.chars().all(plain) {
+        return s.to_string();
+    }
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_paths() {
+        let value = json::object(vec![
+            ("memory", json::object(vec![("used_bytes", 6543210000u64.into())])),
+            ("storage", Json::Array(vec![json::object(vec![("mount", "/".into())])])),
+            ("gpus", Json::Array(Vec::new())),
+            ("terminal", Json::Null),
+            ("os", "Arch Linux".into()),
+        ]);
+        let mut out = String::new();
+        flatten(&mut out, "", &value, &Config::default());
+        assert_eq!(out, "memory.used_bytes=6543210000\nstorage.0.mount=/\nterminal=\nos='Arch Linux'\n");
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote("6.6.1-arch1-1"), "6.6.1-arch1-1");
+        assert_eq!(quote("it's"), "'it'\\''s'");
+        assert_eq!(quote("a\nb"), "'a\nb'");
+        assert_eq!(quote(""), "''");
+    }
+}
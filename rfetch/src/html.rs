@@ -0,0 +1,147 @@
+//! `--format html`: the human output as one self-contained `<div>`, for
+//! embedding in a status page. Each output line is a row, and ANSI colors
+//! become inline-styled spans, so no stylesheet is needed.
+
+use crate::{
+    color::{ColorMode, Rgb, Theme},
+    config::{Align, Config},
+    info::SystemInfo,
+    render,
+};
+
+pub fn render(info: &SystemInfo, config: &Config) -> String {
+    // The page does the coloring, so a pipe or file is no reason to drop
+    // it; only an explicit `never` does.
+    let mut config = config.clone();
+    if config.color != ColorMode::Never {
+        config.color = ColorMode::Always;
+    }
+    // The page lays the block out, not the terminal rfetch ran in.
+    config.align = Align::Left;
+    let text = render::render(info, &config);
+
+    let (background, foreground) = match (config.scheme, config.theme) {
+        // base00 is a scheme's default background and base05 its foreground.
+        (Some(scheme), _) => (scheme[0x00].hex(), scheme[0x05].hex()),
+        (None, Theme::Light) => ("#ffffff".to_string(), "#1e1e1e".to_string()),
+        (None, Theme::Auto | Theme::Dark) => ("#1e1e1e".to_string(), "#d4d4d4".to_string()),
+    };
+    let mut out = format!(
+        "<div class=\"rfetch\" style=\"font-family:monospace;white-space:pre;background:{};color:{};padding:1em\">\n",
+        background, foreground,
+    );
+    for line in text.lines() {
+        // An empty row would collapse to nothing.
+        let row = if line.is_empty() { "&nbsp;".to_string() } else { ansi_to_html(line) };
+        out.push_str(&format!("<div>{}</div>\n", row));
+    }
+    out.push_str("</div>\n");
+    out
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Style {
+    bold: bool,
+    color: Option<Rgb>,
+}
+
+impl Style {
+    /// Applies one SGR sequence's `;`-separated parameters.
+    fn apply(&mut self, params: &str) {
+        let mut codes = params.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.color = Some(Rgb::ansi256(code - 30)),
+                90..=97 => self.color = Some(Rgb::ansi256(code - 90 + 8)),
+                39 => self.color = None,
+                38 => match codes.next() {
+                    Some(5) => self.color = codes.next().map(Rgb::ansi256),
+                    Some(2) => {
+                        let (r, g, b) = (codes.next(), codes.next(), codes.next());
+                        if let (Some(r), Some(g), Some(b)) = (r, g, b) {
+                            self.color = Some(Rgb(r, g, b));
+                        }
+                    }
+                    _ => {}
+                },
+                // Backgrounds and the rest never appear in rfetch's output.
+                _ => {}
+            }
+        }
+    }
+
+    fn css(self) -> String {
+        let mut css = String::new();
+        if let Some(color) = self.color {
+            css.push_str(&format!("color:{}", color.hex()));
+        }
+        if self.bold {
+            if !css.is_empty() {
+                css.push(';');
+            }
+            css.push_str("font-weight:bold");
+        }
+        css
+    }
+}
+
+/// Escapes `line` for HTML and turns its SGR sequences into `<span>`s.
+fn ansi_to_html(line: &str) -> String {
+    let mut out = String::new();
+    let mut style = Style::default();
+    let mut open = false;
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        if let Some(sequence) = rest.strip_prefix("\x1b[")
+            && let Some(end) = sequence.find('m')
+        {
+            let before = style;
+            style.apply(&sequence[..end]);
+            rest = &sequence[end + 1..];
+            if style != before {
+                if open {
+                    out.push_str("</span>");
+                }
+                open = style != Style::default();
+                if open {
+                    out.push_str(&format!("<span style=\"{}\">", style.css()));
+                }
+            }
+            continue;
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // A stray escape that isn't an SGR sequence.
+            '\x1b' => {}
+            c => out.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    if open {
+        out.push_str("</span>");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_become_spans() {
+        assert_eq!(
+            ansi_to_html("\x1b[1;96mme@box\x1b[0m <3"),
+            "<span style=\"color:#00ffff;font-weight:bold\">me@box</span> &lt;3",
+        );
+        assert_eq!(ansi_to_html("\x1b[38;2;255;0;16mx"), "<span style=\"color:#ff0010\">x</span>");
+        assert_eq!(ansi_to_html("\x1b[38;5;196mx\x1b[0m"), "<span style=\"color:#ff0000\">x</span>");
+    }
+}
@@ -0,0 +1,96 @@
+//! Enumeration of hwmon sensor chips under `/sys/class/hwmon`, shared by
+//! the sensor-reading fields.
+
+use std::{fs, path::PathBuf};
+
+pub struct Chip {
+    /// Driver name from the chip's `name` file, e.g. `k10temp`.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+pub struct Sensor {
+    /// From `<kind><n>_label`, or `<kind><n>` when the driver gives none.
+    pub label: String,
+    /// True when the driver provided a `_label` file.
+    pub labelled: bool,
+    /// Raw `<kind><n>_input` value, in the kind's sysfs unit.
+    pub input: i64,
+}
+
+pub fn chips() -> Vec<Chip> {
+    let Ok(entries) = fs::read_dir("/sys/class/hwmon") else {
+        return Vec::new();
+    };
+    let mut chips: Vec<Chip> = entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            let name = fs::read_to_string(path.join("name"))
+                .map(|n| n.trim().to_string())
+                .unwrap_or_default();
+            Chip { name, path }
+        })
+        .collect();
+    chips.sort_by(|a, b| a.path.cmp(&b.path));
+    chips
+}
+
+/// Every temperature sensor, as `chip/label` (the form `temp_sensor`
+/// takes) and degrees Celsius.
+pub fn temperatures() -> Vec<(String, f64)> {
+    chips()
+        .into_iter()
+        .flat_map(|chip| {
+            chip.sensors("temp")
+                .into_iter()
+                .map(move |sensor| (format!("{}/{}", chip.name, sensor.label), sensor.input as f64 / 1000.0))
+        })
+        .collect()
+}
+
+/// How good a guess `chip/label` is for the CPU temperature, best first;
+/// `None` for sensors that aren't the CPU's.
+pub fn cpu_sensor_rank(name: &str) -> Option<u8> {
+    let (chip, label) = name.split_once('/')?;
+    match (chip, label) {
+        // Tctl carries a fan-curve offset on some Ryzens; Tdie doesn't.
+        ("k10temp" | "zenpower", "Tdie") => Some(0),
+        ("k10temp" | "zenpower", "Tctl") => Some(1),
+        ("coretemp", l) if l.starts_with("Package id") => Some(0),
+        ("cpu_thermal" | "soc_thermal" | "cpu0_thermal", _) => Some(2),
+        ("acpitz", _) => Some(3),
+        _ => None,
+    }
+}
+
+impl Chip {
+    /// Readings of one sensor kind (`temp`, `fan`, ...), in index order.
+    pub fn sensors(&self, kind: &str) -> Vec<Sensor> {
+        let Ok(entries) = fs::read_dir(&self.path) else {
+            return Vec::new();
+        };
+        let mut indices: Vec<u32> = entries
+            .flatten()
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.strip_prefix(kind)?.strip_suffix("_input")?.parse().ok()
+            })
+            .collect();
+        indices.sort_unstable();
+
+        indices
+            .into_iter()
+            .filter_map(|n| {
+                let read = |suffix: &str| fs::read_to_string(self.path.join(format!("{}{}_{}", kind, n, suffix)));
+                let input = read("input").ok()?.trim().parse().ok()?;
+                let label = read("label").ok().map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+                Some(Sensor {
+                    labelled: label.is_some(),
+                    label: label.unwrap_or_else(|| format!("{}{}", kind, n)),
+                    input,
+                })
+            })
+            .collect()
+    }
+}
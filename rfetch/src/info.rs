@@ -0,0 +1,293 @@
+//! The structured result of a fetch. Collectors fill it in with raw values
+//! (bytes, seconds) and each output format decides how to present them.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct SystemInfo {
+    pub user: String,
+    pub host: String,
+    pub os: String,
+    /// 1 or 2 under Windows Subsystem for Linux.
+    pub wsl: Option<u8>,
+    /// Running in a chroot, so `/proc` (init, uptime) describes the host.
+    pub chroot: bool,
+    /// The machine's model from DMI, e.g. `ThinkPad X1 Carbon Gen 9`.
+    pub model: Option<String>,
+    pub init: String,
+    pub kernel: String,
+    /// Compile date of the kernel, `YYYY-MM-DD`, with `kernel_build`.
+    pub kernel_build: Option<String>,
+    /// Seconds since boot.
+    pub uptime: Option<u64>,
+    /// Share of CPU time spent idle since boot, in percent.
+    pub idle: Option<f64>,
+    /// Boot time as a Unix timestamp (`btime` in `/proc/stat`).
+    pub boot_time: Option<u64>,
+    pub shell: String,
+    pub display_manager: Option<String>,
+    pub terminal: Option<String>,
+    pub terminal_font: Option<String>,
+    /// tmux, screen or zellij when rfetch runs inside one.
+    pub multiplexer: Option<String>,
+    /// Columns and rows of the terminal rfetch prints to.
+    pub terminal_size: Option<(u16, u16)>,
+    /// Keyboard lock LEDs, when the kernel exposes any.
+    pub locks: Option<Locks>,
+    pub gpus: Vec<Gpu>,
+    pub memory: Option<Usage>,
+    /// Installed RAM modules from dmidecode, identical ones grouped.
+    pub memory_modules: Vec<MemoryModules>,
+    pub swap: Option<Usage>,
+    /// Per-device breakdown from `/proc/swaps`, only with `swap_detail`.
+    pub swap_devices: Vec<SwapDevice>,
+    pub storage: Vec<Storage>,
+    /// The I/O scheduler of the disk holding `/`.
+    pub scheduler: Option<Scheduler>,
+    pub services: Option<Services>,
+    /// Installed packages, one entry per source.
+    pub packages: Vec<PackageCount>,
+    /// Pending package updates.
+    pub updates: Option<usize>,
+    /// OpenGL version and renderer, e.g. `4.6 (AMD Radeon RX 6700 XT)`.
+    pub opengl: Option<String>,
+    /// Vulkan API version and device, e.g. `1.3.246 (AMD Radeon RX 6700 XT)`.
+    pub vulkan: Option<String>,
+    /// Desktop UI scale factor, e.g. `2x`.
+    pub scale: Option<String>,
+    /// CPU vendor and microarchitecture, e.g. `AMD (Zen 3)`.
+    pub microarch: Option<String>,
+    /// CPU sockets and NUMA nodes.
+    pub topology: Option<Topology>,
+    /// CPU temperature in degrees Celsius.
+    pub cpu_temp: Option<f64>,
+    /// Average CPU package power in watts over a short sample.
+    pub cpu_power: Option<f64>,
+    /// Whether the machine runs on AC or battery.
+    pub power: Option<PowerSource>,
+    /// Whether the CPU is thermal-throttling right now.
+    pub throttling: Option<bool>,
+    /// Connected monitors.
+    pub displays: Vec<Display>,
+    /// cpufreq governor and energy preference, e.g. `powersave (balance_power)`.
+    pub governor: Option<String>,
+    /// Kernel command line parameters that passed `cmdline_filter`.
+    pub cmdline: Option<String>,
+    /// The running notification daemon, e.g. `dunst`.
+    pub notifications: Option<String>,
+    /// Wayland compositor and version, e.g. `Hyprland 0.41.2`.
+    pub compositor: Option<String>,
+    /// Active MAC frameworks, e.g. `SELinux (Enforcing)`.
+    pub security: Option<String>,
+    pub fans: Vec<Fan>,
+    pub kernel_modules: Option<KernelModules>,
+    /// System-wide open file handles.
+    pub files: Option<FileHandles>,
+    /// Bits in the kernel's entropy pool.
+    pub entropy: Option<u64>,
+    /// The IPv4 default gateway, e.g. `192.168.1.1`.
+    pub gateway: Option<String>,
+    /// Configured DNS servers, e.g. `192.168.1.1`, in order.
+    pub dns: Vec<String>,
+    /// The interface carrying the default route.
+    pub network: Option<Network>,
+    /// The git repository rfetch was run in, if any.
+    pub git: Option<GitRepo>,
+    /// Pressure stall information, only with the pressure field.
+    pub pressure: Option<Pressure>,
+    /// 1, 5 and 15 minute load averages.
+    pub loadavg: Option<[f64; 3]>,
+    /// Output of each `[[custom]]` command listed in the fields, by name.
+    /// Commands that failed or printed nothing are left out.
+    pub custom: BTreeMap<String, String>,
+    /// First line of each `[[file]]` listed in the fields, by name; `None`
+    /// when the file is missing, unreadable or empty.
+    pub file_fields: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Gpu {
+    pub name: String,
+    /// PCI address, e.g. `0000:01:00.0`.
+    pub pci: String,
+    pub kind: GpuKind,
+    /// Whether the firmware initialised the display on this GPU.
+    pub boot_vga: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuKind {
+    Integrated,
+    Discrete,
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageCount {
+    /// The package manager or format, e.g. `pacman`, `flatpak`, `aur`.
+    pub source: String,
+    pub count: usize,
+}
+
+/// systemd unit counts.
+#[derive(Debug, Clone, Copy)]
+pub struct Services {
+    pub running: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitRepo {
+    /// Branch name, or the short commit hash when HEAD is detached.
+    pub branch: String,
+    /// `None` when the dirty check was skipped or failed.
+    pub dirty: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Display {
+    /// DRM connector, e.g. `HDMI-A-1` or `eDP-1`.
+    pub connector: String,
+    pub width: u32,
+    pub height: u32,
+    /// Physical width and height in millimetres, from the EDID.
+    pub size_mm: Option<(u32, u32)>,
+}
+
+impl Display {
+    /// Diagonal in inches and horizontal pixels per inch.
+    pub fn physical(&self) -> Option<(f64, f64)> {
+        let (w, h) = self.size_mm?;
+        let inches = (w as f64).hypot(h as f64) / 25.4;
+        Some((inches, self.width as f64 * 25.4 / w as f64))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Network {
+    pub interface: String,
+    /// `wired`, `wifi` or `virtual` (VPN tunnels, bridges, ...).
+    pub kind: String,
+    /// Wi-Fi only.
+    pub ssid: Option<String>,
+    /// Wi-Fi signal level in dBm, e.g. `-52`.
+    pub signal_dbm: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheduler {
+    /// Kernel name of the disk, e.g. `nvme0n1`.
+    pub device: String,
+    pub active: String,
+    /// Every scheduler the kernel offers for the disk, the active one
+    /// included.
+    pub available: Vec<String>,
+}
+
+/// Which lock keys are on. Each is lit when any keyboard's LED is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Locks {
+    pub caps: bool,
+    pub num: bool,
+    pub scroll: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Topology {
+    /// Distinct `physical id`s in `/proc/cpuinfo`; `None` where the
+    /// architecture doesn't report them.
+    pub sockets: Option<usize>,
+    pub numa_nodes: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FileHandles {
+    pub open: u64,
+    /// `fs.file-max`.
+    pub max: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct KernelModules {
+    /// Modules in `/proc/modules`.
+    pub count: usize,
+    /// The `modules_top` biggest, largest first, as name and bytes.
+    pub largest: Vec<(String, u64)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fan {
+    pub label: Option<String>,
+    pub rpm: u64,
+}
+
+/// `count` identical RAM modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryModules {
+    pub count: usize,
+    /// Bytes per module.
+    pub size: u64,
+    /// e.g. `DDR4`.
+    pub kind: Option<String>,
+    /// Rated speed in MT/s.
+    pub speed: Option<u32>,
+}
+
+/// PSI `some avg10`: the share of the last 10 seconds in which at least one
+/// task stalled on the resource, in percent.
+#[derive(Debug, Clone, Copy)]
+pub struct Pressure {
+    pub cpu: Option<f64>,
+    pub memory: Option<f64>,
+}
+
+/// A used/total pair, in bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct Usage {
+    pub used: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwapDevice {
+    pub name: String,
+    /// `partition`, `file` or `zram`.
+    pub kind: String,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Clone)]
+pub struct Storage {
+    pub mount: String,
+    /// `None` when no disk backs the mount.
+    pub usage: Option<Usage>,
+    pub filesystem: Option<String>,
+    /// Short annotations shown after the mount, e.g. `btrfs, compressed`.
+    pub notes: Vec<String>,
+    /// Where a network mount points, e.g. `server:/export`. Its space is
+    /// never queried, so `usage` is `None`.
+    pub source: Option<String>,
+    /// Inodes in use and in total, only with `storage_inodes` and on
+    /// filesystems with a fixed inode table.
+    pub inodes: Option<Usage>,
+}
+
+impl Storage {
+    /// A mount we couldn't find a disk for.
+    pub fn unavailable(mount: &str) -> Storage {
+        Storage {
+            mount: mount.to_string(),
+            usage: None,
+            filesystem: None,
+            notes: Vec::new(),
+            source: None,
+            inodes: None,
+        }
+    }
+}
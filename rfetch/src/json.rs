@@ -0,0 +1,383 @@
+//! `--format json`: the whole `SystemInfo` as a JSON object.
+
+use std::fmt::Write;
+
+use crate::{
+    info::{GpuKind, PowerSource, SystemInfo, Usage},
+    kernel,
+};
+
+/// A JSON value. Objects keep their keys in insertion order.
+#[derive(Debug, Clone)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(u64),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Json {
+        Json::Str(s.to_string())
+    }
+}
+
+impl From<&String> for Json {
+    fn from(s: &String) -> Json {
+        Json::Str(s.clone())
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Json {
+        Json::Int(n)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(n: u32) -> Json {
+        Json::Int(n as u64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Json {
+        Json::Int(n as u64)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Json {
+        Json::Bool(b)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+pub fn object(entries: Vec<(&str, Json)>) -> Json {
+    Json::Object(entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn usage(usage: Usage) -> Vec<(&'static str, Json)> {
+    vec![("used_bytes", usage.used.into()), ("total_bytes", usage.total.into())]
+}
+
+/// The JSON shape is described by hand in `schema.rs`; its tests fail when
+/// the two drift apart.
+pub fn to_json(info: &SystemInfo) -> Json {
+    let gpus = info
+        .gpus
+        .iter()
+        .map(|gpu| {
+            let kind = match gpu.kind {
+                GpuKind::Integrated => "integrated",
+                GpuKind::Discrete => "discrete",
+                GpuKind::Unknown => "unknown",
+            };
+            object(vec![
+                ("name", (&gpu.name).into()),
+                ("pci", (&gpu.pci).into()),
+                ("kind", kind.into()),
+                ("boot_vga", gpu.boot_vga.into()),
+            ])
+        })
+        .collect();
+
+    let swap_devices = info
+        .swap_devices
+        .iter()
+        .map(|device| {
+            let mut entries = vec![("name", (&device.name).into()), ("kind", (&device.kind).into())];
+            entries.extend(usage(device.usage));
+            object(entries)
+        })
+        .collect();
+
+    let storage = info
+        .storage
+        .iter()
+        .map(|storage| {
+            let mut entries = vec![("mount", (&storage.mount).into())];
+            match storage.usage {
+                Some(u) => entries.extend(usage(u)),
+                None => entries.extend([("used_bytes", Json::Null), ("total_bytes", Json::Null)]),
+            }
+            entries.push(("filesystem", storage.filesystem.as_ref().into()));
+            entries.push(("notes", Json::Array(storage.notes.iter().map(Into::into).collect())));
+            entries.push(("source", storage.source.as_ref().into()));
+            entries.push(("inodes_used", storage.inodes.map(|i| i.used).into()));
+            entries.push(("inodes_total", storage.inodes.map(|i| i.total).into()));
+            object(entries)
+        })
+        .collect();
+
+    object(vec![
+        ("user", (&info.user).into()),
+        ("host", (&info.host).into()),
+        ("os", (&info.os).into()),
+        ("wsl", info.wsl.map(u64::from).into()),
+        ("chroot", info.chroot.into()),
+        ("model", info.model.as_ref().into()),
+        ("init", (&info.init).into()),
+        ("kernel", (&info.kernel).into()),
+        ("kernel_commit", kernel::commit(&info.kernel).into()),
+        ("kernel_build_date", info.kernel_build.as_ref().into()),
+        ("uptime_seconds", info.uptime.into()),
+        ("idle_percent", info.idle.map_or(Json::Null, Json::Float)),
+        ("boot_time", info.boot_time.into()),
+        ("shell", (&info.shell).into()),
+        ("display_manager", info.display_manager.as_ref().into()),
+        ("terminal", info.terminal.as_ref().into()),
+        ("terminal_font", info.terminal_font.as_ref().into()),
+        ("multiplexer", info.multiplexer.as_ref().into()),
+        (
+            "terminal_size",
+            info.terminal_size.map_or(Json::Null, |(columns, rows)| {
+                object(vec![("columns", u64::from(columns).into()), ("rows", u64::from(rows).into())])
+            }),
+        ),
+        (
+            "locks",
+            info.locks.map_or(Json::Null, |l| {
+                object(vec![("caps", l.caps.into()), ("num", l.num.into()), ("scroll", l.scroll.into())])
+            }),
+        ),
+        ("gpus", Json::Array(gpus)),
+        ("memory", info.memory.map_or(Json::Null, |u| object(usage(u)))),
+        (
+            "memory_modules",
+            Json::Array(
+                info.memory_modules
+                    .iter()
+                    .map(|m| {
+                        object(vec![
+                            ("count", m.count.into()),
+                            ("size_bytes", m.size.into()),
+                            ("type", m.kind.as_ref().into()),
+                            ("speed_mts", m.speed.into()),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+        ("swap", info.swap.map_or(Json::Null, |u| object(usage(u)))),
+        ("swap_devices", Json::Array(swap_devices)),
+        ("storage", Json::Array(storage)),
+        (
+            "scheduler",
+            info.scheduler.as_ref().map_or(Json::Null, |s| {
+                object(vec![
+                    ("device", (&s.device).into()),
+                    ("active", (&s.active).into()),
+                    ("available", Json::Array(s.available.iter().map(Into::into).collect())),
+                ])
+            }),
+        ),
+        (
+            "services",
+            info.services.map_or(Json::Null, |s| {
+                object(vec![("running", s.running.into()), ("failed", s.failed.into())])
+            }),
+        ),
+        (
+            "packages",
+            Json::Array(
+                info.packages
+                    .iter()
+                    .map(|p| object(vec![("source", (&p.source).into()), ("count", p.count.into())]))
+                    .collect(),
+            ),
+        ),
+        ("updates", info.updates.into()),
+        ("cpu_temp_celsius", info.cpu_temp.map_or(Json::Null, Json::Float)),
+        ("cpu_power_watts", info.cpu_power.map_or(Json::Null, Json::Float)),
+        (
+            "power",
+            info.power
+                .map(|p| match p {
+                    PowerSource::Ac => "ac",
+                    PowerSource::Battery => "battery",
+                })
+                .into(),
+        ),
+        ("throttling", info.throttling.into()),
+        (
+            "displays",
+            Json::Array(
+                info.displays
+                    .iter()
+                    .map(|d| {
+                        object(vec![
+                            ("connector", (&d.connector).into()),
+                            ("width", d.width.into()),
+                            ("height", d.height.into()),
+                            ("width_mm", d.size_mm.map(|(w, _)| w).into()),
+                            ("height_mm", d.size_mm.map(|(_, h)| h).into()),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+        ("opengl", info.opengl.as_ref().into()),
+        ("vulkan", info.vulkan.as_ref().into()),
+        ("scale", info.scale.as_ref().into()),
+        ("microarch", info.microarch.as_ref().into()),
+        (
+            "topology",
+            info.topology.map_or(Json::Null, |t| {
+                object(vec![("sockets", t.sockets.into()), ("numa_nodes", t.numa_nodes.into())])
+            }),
+        ),
+        ("governor", info.governor.as_ref().into()),
+        ("cmdline", info.cmdline.as_ref().into()),
+        ("notifications", info.notifications.as_ref().into()),
+        ("compositor", info.compositor.as_ref().into()),
+        ("security", info.security.as_ref().into()),
+        (
+            "files",
+            info.files.map_or(Json::Null, |f| object(vec![("open", f.open.into()), ("max", f.max.into())])),
+        ),
+        ("entropy_bits", info.entropy.into()),
+        (
+            "kernel_modules",
+            info.kernel_modules.as_ref().map_or(Json::Null, |m| {
+                let largest = m
+                    .largest
+                    .iter()
+                    .map(|(name, size)| object(vec![("name", name.into()), ("size_bytes", (*size).into())]))
+                    .collect();
+                object(vec![("count", m.count.into()), ("largest", Json::Array(largest))])
+            }),
+        ),
+        ("gateway", info.gateway.as_ref().into()),
+        ("dns_servers", Json::Array(info.dns.iter().map(Into::into).collect())),
+        (
+            "network",
+            info.network.as_ref().map_or(Json::Null, |n| {
+                object(vec![
+                    ("interface", (&n.interface).into()),
+                    ("kind", (&n.kind).into()),
+                    ("ssid", n.ssid.as_ref().into()),
+                    ("signal_dbm", n.signal_dbm.map_or(Json::Null, |dbm| Json::Float(dbm.into()))),
+                ])
+            }),
+        ),
+        (
+            "fans",
+            Json::Array(
+                info.fans
+                    .iter()
+                    .map(|fan| object(vec![("label", fan.label.as_ref().into()), ("rpm", fan.rpm.into())]))
+                    .collect(),
+            ),
+        ),
+        (
+            "git",
+            info.git.as_ref().map_or(Json::Null, |git| {
+                object(vec![("branch", (&git.branch).into()), ("dirty", git.dirty.into())])
+            }),
+        ),
+        (
+            "pressure",
+            info.pressure.map_or(Json::Null, |p| {
+                object(vec![
+                    ("cpu", p.cpu.map_or(Json::Null, Json::Float)),
+                    ("memory", p.memory.map_or(Json::Null, Json::Float)),
+                ])
+            }),
+        ),
+        (
+            "loadavg",
+            info.loadavg
+                .map_or(Json::Null, |l| Json::Array(l.iter().map(|&v| Json::Float(v)).collect())),
+        ),
+        (
+            "custom",
+            Json::Object(info.custom.iter().map(|(name, output)| (name.clone(), output.into())).collect()),
+        ),
+        (
+            "file_fields",
+            Json::Object(info.file_fields.iter().map(|(name, line)| (name.clone(), line.as_ref().into())).collect()),
+        ),
+    ])
+}
+
+/// Pretty-printed JSON with a trailing newline.
+pub fn render(info: &SystemInfo) -> String {
+    pretty(&to_json(info))
+}
+
+/// `value` pretty-printed, with a trailing newline.
+pub fn pretty(value: &Json) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, 0);
+    out.push('\n');
+    out
+}
+
+fn write_value(out: &mut String, value: &Json, indent: usize) {
+    let pad = |n: usize| "  ".repeat(n);
+    match value {
+        Json::Null => out.push_str("null"),
+        Json::Bool(b) => {
+            let _ = write!(out, "{}", b);
+        }
+        Json::Int(n) => {
+            let _ = write!(out, "{}", n);
+        }
+        Json::Float(f) if f.is_finite() => {
+            let _ = write!(out, "{}", f);
+        }
+        Json::Float(_) => out.push_str("null"),
+        Json::Str(s) => write_string(out, s),
+        Json::Array(items) if items.is_empty() => out.push_str("[]"),
+        Json::Array(items) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                out.push_str(&pad(indent + 1));
+                write_value(out, item, indent + 1);
+                out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&pad(indent));
+            out.push(']');
+        }
+        Json::Object(entries) if entries.is_empty() => out.push_str("{}"),
+        Json::Object(entries) => {
+            out.push_str("{\n");
+            for (i, (key, item)) in entries.iter().enumerate() {
+                out.push_str(&pad(indent + 1));
+                write_string(out, key);
+                out.push_str(": ");
+                write_value(out, item, indent + 1);
+                out.push_str(if i + 1 < entries.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&pad(indent));
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
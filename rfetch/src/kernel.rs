@@ -0,0 +1,97 @@
+//! Parsing of the kernel release and `/proc/version`, for people running
+//! their own builds: where the local version starts, the git commit that
+//! `CONFIG_LOCALVERSION_AUTO` appends, and when the kernel was compiled.
+
+/// Splits a release into its version and local-version suffix, e.g.
+/// `6.9.0-mine+` into `6.9.0` and `-mine+`.
+pub fn split_release(release: &str) -> (&str, &str) {
+    let end = release.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(release.len());
+    release.split_at(end)
+}
+
+/// The short commit in a release like `6.10.0-rc3-00042-gabc1234-dirty`:
+/// a `g` and at least seven hex digits, as `git describe` writes it.
+pub fn commit(release: &str) -> Option<&str> {
+    let (_, local) = split_release(release);
+    local.split(['-', '+']).find_map(|part| {
+        let hash = part.strip_prefix('g')?;
+        (hash.len() >= 7 && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(hash)
+    })
+}
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// The compile date at the end of `/proc/version`, as `YYYY-MM-DD`. Builds
+/// stamp it as `date` prints it (`Mon May 20 14:36:14 UTC 2024`), as RFC
+/// 2822 (`Thu, 30 May 2024 19:43:02 +0000`), as Debian's `(2024-05-03)` or,
+/// when `KBUILD_BUILD_TIMESTAMP` is an epoch, as `@1716200000`. `@0`, used
+/// by reproducible builds, gives `None`.
+pub fn build_date(version: &str) -> Option<String> {
+    // The build number and flags come after the last `#`, and the date
+    // after those; the compiler and host before would only confuse us.
+    let stamp = &version[version.rfind('#')?..];
+    let words: Vec<&str> = stamp
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c| c == ',' || c == '(' || c == ')'))
+        .collect();
+
+    if let Some(epoch) = words.last().and_then(|w| w.strip_prefix('@')) {
+        return epoch.parse().ok().filter(|&secs| secs > 0).and_then(utc_date);
+    }
+    if let Some(date) = words.iter().rev().find(|w| is_iso_date(w)) {
+        return Some(date.to_string());
+    }
+    let month = words.iter().rposition(|w| MONTHS.contains(w))?;
+    let index = MONTHS.iter().position(|m| *m == words[month])? + 1;
+    let number = |w: &&str| w.parse::<u32>().ok();
+    let day = |i: usize| words.get(i).and_then(number).filter(|d| (1..=31).contains(d));
+    // The day is right before the month in RFC 2822 and right after it
+    // otherwise; the year is the first four-digit word after the month.
+    let day = day(month + 1).or_else(|| day(month.checked_sub(1)?))?;
+    let year = words[month + 1..].iter().filter(|w| w.len() == 4).find_map(number)?;
+    Some(format!("{:04}-{:02}-{:02}", year, index, day))
+}
+
+fn is_iso_date(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| if i == 4 || i == 7 { *b == b'-' } else { b.is_ascii_digit() })
+}
+
+fn utc_date(secs: i64) -> Option<String> {
+    let time = secs as libc::time_t;
+    // SAFETY: `tm` is plain data and gmtime_r only writes into it.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::gmtime_r(&time, &mut tm) }.is_null() {
+        return None;
+    }
+    Some(format!("{:04}-{:02}-{:02}", tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_parts() {
+        assert_eq!(split_release("6.9.0-mine+"), ("6.9.0", "-mine+"));
+        assert_eq!(split_release("6.6.1"), ("6.6.1", ""));
+        assert_eq!(commit("6.10.0-rc3-00042-gabc1234-dirty"), Some("abc1234"));
+        assert_eq!(commit("6.9.0-g1234567+"), Some("1234567"));
+        assert_eq!(commit("6.9.1-arch1-1"), None);
+        assert_eq!(commit("6.9.0-gentoo"), None);
+    }
+
+    #[test]
+    fn build_dates() {
+        let date = |stamp: &str| build_date(&format!("Linux version 6.9.0 (me@box) (gcc 14.1.1) {}", stamp));
+        assert_eq!(date("#3 SMP PREEMPT_DYNAMIC Mon May 20 14:36:14 UTC 2024").as_deref(), Some("2024-05-20"));
+        assert_eq!(date("#1-NixOS SMP PREEMPT_DYNAMIC Sat Jun  1 09:05:00 CEST 2024").as_deref(), Some("2024-06-01"));
+        assert_eq!(date("#1 SMP PREEMPT_DYNAMIC Thu, 30 May 2024 19:43:02 +0000").as_deref(), Some("2024-05-30"));
+        assert_eq!(date("#1 SMP PREEMPT_DYNAMIC Debian 6.1.90-1 (2024-05-03)").as_deref(), Some("2024-05-03"));
+        assert_eq!(date("#35-Ubuntu SMP PREEMPT_DYNAMIC Mon May 20 15:51:52 UTC 2024").as_deref(), Some("2024-05-20"));
+        assert_eq!(date("#1 SMP @1716200000").as_deref(), Some("2024-05-20"));
+        assert_eq!(date("#1 SMP PREEMPT_DYNAMIC @0"), None);
+        assert_eq!(date("#1 SMP"), None);
+    }
+}
@@ -1,78 +1,537 @@
-use std::{fs, process::Command, path::Path};
+mod cache;
+mod cli;
+mod color;
+mod compat;
+mod completions;
+mod config;
+mod fields;
+mod flat;
+mod html;
+mod hwmon;
+mod info;
+mod json;
+mod kernel;
+mod prometheus;
+mod render;
+mod schema;
+mod serve;
+mod summary;
+mod template;
+mod toml;
+mod units;
+mod watch;
+mod width;
+
+use std::{
+    fs,
+    io::{self, Write},
+    panic::{self, AssertUnwindSafe},
+    net::Ipv4Addr,
+    path::Path,
+    process::Command,
+    sync::atomic::{AtomicBool, Ordering},
+};
 use sysinfo::{System, Disks};
 
+use cli::{Args, Format};
+use config::Config;
+use config::{GpuMode, HostnameStyle, StorageMounts, StorageSort};
+use info::{
+    Display, Fan, FileHandles, GitRepo, Gpu, GpuKind, KernelModules, Locks, MemoryModules, Network, Pressure, PackageCount, PowerSource, Scheduler, Services, Storage, SwapDevice, SystemInfo, Topology, Usage,
+};
+
 fn main () {
-    let user = get_username();
-    let host = get_hostname();
-    let os = get_os();
-    let init = detect_init();
-    let kernel = get_kernel();
-    let uptime = get_uptime();
-    let mem = get_memory();
-    let swap = get_swap();
-    let _storage_root = get_storage("/");
-    let shell = get_shell();
-
-    println!("{}@{}", user, host);
-    println!("----------");
-    println!("OS      : {}", os);
-    println!("Init    : {}", init);
-    println!("Kernel  : {}", kernel);
-    println!("Uptime  : {}", uptime);
-    println!("Shell   : {}", shell);
-    println!("Memory  : {}", mem);
-    println!("Swap    : {}", swap);
-    println!("Storage : {}", _storage_root);
+    let mut argv = std::env::args();
+    let argv0 = argv.next().unwrap_or_default();
+    let argv: Vec<String> = argv.collect();
+    let argv = match compat::Tool::from_argv0(&argv0) {
+        Some(tool) => compat::translate(tool, argv),
+        None => argv,
+    };
+    let args = match Args::parse(argv) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("rfetch: {}", e);
+            eprintln!("{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    if let Some(shell) = args.completions {
+        print!("{}", completions::generate(shell));
+        return;
+    }
+    if args.schema {
+        print!("{}", schema::render());
+        return;
+    }
+    if args.sensors {
+        for (name, celsius) in hwmon::temperatures() {
+            println!("{:<24} {:.1}°C", name, celsius);
+        }
+        return;
+    }
+
+    // A collector bug should cost one field, not the whole fetch: `guarded`
+    // catches the panic, and this hook keeps the report to a single line.
+    panic::set_hook(Box::new(|info| {
+        eprintln!("rfetch: internal error: {}", info);
+    }));
+
+    let mut config = Config::resolve(&args);
+    // Only colored human output uses the palette, so only then is the
+    // terminal worth asking.
+    if config.format == Format::Human && config.color.enabled() && config.scheme.is_none() {
+        config.theme = config.theme.detect();
+    }
+    let info = collect(&config);
+
+    if let Some(addr) = &args.serve {
+        if let Err(e) = serve::serve(addr, &config, info, refresh_volatile) {
+            eprintln!("rfetch: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.watch {
+        let interval = std::time::Duration::from_secs(args.refresh.unwrap_or(2));
+        // Like one-shot output below, a closed stdout just ends the run.
+        let _ = watch::watch(interval, &config, info, refresh_volatile, render_output);
+        return;
+    }
+
+    let output = render_output(&info, &config);
+    if let Some(path) = &args.output {
+        if let Err(e) = write_output(path, &output) {
+            eprintln!("rfetch: {}", e);
+            std::process::exit(1);
+        }
+    } else if !args.quiet {
+        // Ignore write errors: `rfetch | head -1` closing the pipe early is fine.
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(output.as_bytes()).and_then(|()| stdout.flush());
+    }
+
+    // The exit status contract: 2 for a bad command line or config, 1 for
+    // a failed collector under --strict, 0 otherwise.
+    if config.errored {
+        std::process::exit(2);
+    }
+    if args.strict && COLLECTOR_FAILED.load(Ordering::Relaxed) {
+        std::process::exit(1);
+    }
+}
+
+/// Writes `--output`. The directory must already exist: a typo in a
+/// service's path should fail loudly, not grow a new tree.
+fn write_output(path: &Path, output: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty())
+        && !dir.is_dir()
+    {
+        return Err(format!("can't write `{}`: directory `{}` doesn't exist", path.display(), dir.display()));
+    }
+    fs::write(path, output).map_err(|e| format!("can't write `{}`: {}", path.display(), e))
+}
+
+/// rfetch's own version, with the commit it was built from when known:
+/// `0.1.0 (5d10dc5)`.
+fn version() -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    match option_env!("RFETCH_GIT_HASH") {
+        Some(hash) => format!("{} ({})", version, hash),
+        None => version.to_string(),
+    }
+}
+
+fn render_output(info: &SystemInfo, config: &Config) -> String {
+    match config.format {
+        Format::Human => render::render(info, config),
+        Format::Prometheus => prometheus::render(info),
+        Format::Json => json::render(info),
+        Format::Html => html::render(info, config),
+        Format::Flat => flat::render(info, config),
+        Format::Summary => summary::render(info, config),
+    }
+}
+
+fn collect(config: &Config) -> SystemInfo {
+    let wants = |name: &str| config.fields.iter().any(|f| f == name);
+    // Default fields are collected for JSON even when not shown, except
+    // under --fast, which only pays for what it shows.
+    let full = !config.fast;
+    let unknown = || "unknown".to_string();
+    let init = if full { guarded(unknown(), detect_init) } else { unknown() };
+    let terminal = if full { guarded(None, get_terminal) } else { None };
+    let terminal_font = guarded(None, || terminal.as_deref().and_then(get_terminal_font));
+    let mut info = SystemInfo {
+        user: guarded(unknown(), get_username),
+        host: guarded(unknown(), || get_hostname(config.hostname, full)),
+        os: guarded(unknown(), || get_os(full)),
+        wsl: if full { guarded(None, || wsl_version_in(Path::new("/"))) } else { None },
+        chroot: full && guarded(None, || chroot_in(Path::new("/"))).unwrap_or(false),
+        model: if wants("host") { guarded(None, get_model) } else { None },
+        services: if wants("services") && init.starts_with("systemd") {
+            guarded(None, get_services)
+        } else {
+            None
+        },
+        updates: if wants("updates") {
+            guarded(None, || {
+                let ttl = std::time::Duration::from_secs(config.updates_ttl);
+                cache::cached("updates", ttl, || get_updates().map(|n| n.to_string()))?
+                    .parse()
+                    .ok()
+            })
+        } else {
+            None
+        },
+        git: if wants("git") {
+            guarded(None, || get_git(config.git_dirty))
+        } else {
+            None
+        },
+        packages: if wants("packages") { guarded(Vec::new(), || get_packages(config)) } else { Vec::new() },
+        opengl: if wants("opengl") { guarded(None, get_opengl) } else { None },
+        vulkan: if wants("vulkan") { guarded(None, get_vulkan) } else { None },
+        scale: if wants("scale") { guarded(None, get_scale) } else { None },
+        cpu_temp: if wants("cpu_temp") { guarded(None, || get_cpu_temp(config)) } else { None },
+        throttling: if wants("thermal") { guarded(None, get_throttling) } else { None },
+        displays: if wants("displays") || wants("monitor") { guarded(Vec::new(), get_displays) } else { Vec::new() },
+        microarch: if wants("microarch") { guarded(None, get_microarch) } else { None },
+        topology: if wants("topology") { guarded(None, get_topology) } else { None },
+        scheduler: if wants("scheduler") { guarded(None, get_scheduler) } else { None },
+        governor: if wants("governor") { guarded(None, get_governor) } else { None },
+        cmdline: if wants("cmdline") { guarded(None, || get_cmdline(&config.cmdline_filter)) } else { None },
+        notifications: if wants("notifications") { guarded(None, get_notification_daemon) } else { None },
+        compositor: if wants("compositor") { guarded(None, get_compositor) } else { None },
+        security: if wants("security") { guarded(None, get_security) } else { None },
+        memory_modules: if wants("ram_modules") && is_root() {
+            guarded(Vec::new(), get_memory_modules)
+        } else {
+            Vec::new()
+        },
+        fans: if wants("fan") { guarded(Vec::new(), get_fans) } else { Vec::new() },
+        files: if wants("files") { guarded(None, get_file_handles) } else { None },
+        entropy: if wants("entropy") { guarded(None, get_entropy) } else { None },
+        kernel_modules: if wants("modules") {
+            guarded(None, || get_kernel_modules(config.modules_top))
+        } else {
+            None
+        },
+        multiplexer: if wants("multiplexer") { guarded(None, get_multiplexer) } else { None },
+        terminal_size: if wants("term_size") { guarded(None, width::terminal_size) } else { None },
+        locks: if wants("locks") { guarded(None, || get_locks_in(Path::new("/"))) } else { None },
+        gateway: if wants("gateway") {
+            guarded(None, || default_route().map(|(_, gateway)| gateway.to_string()))
+        } else {
+            None
+        },
+        network: if wants("network") { guarded(None, get_network) } else { None },
+        dns: if wants("dns") { guarded(Vec::new(), get_dns) } else { Vec::new() },
+        init,
+        kernel: guarded(unknown(), get_kernel),
+        kernel_build: if config.kernel_build { guarded(None, get_kernel_build) } else { None },
+        uptime: None,
+        idle: None,
+        boot_time: if full { guarded(None, get_boot_time) } else { None },
+        shell: if full { guarded(unknown(), get_shell) } else { unknown() },
+        display_manager: if full { guarded(None, get_display_manager) } else { None },
+        terminal,
+        terminal_font,
+        gpus: if full {
+            guarded(Vec::new(), || select_gpus(get_gpus(), config.gpu.unwrap_or_else(default_gpu_mode)))
+        } else {
+            Vec::new()
+        },
+        memory: None,
+        swap: None,
+        swap_devices: Vec::new(),
+        storage: Vec::new(),
+        loadavg: None,
+        pressure: None,
+        cpu_power: None,
+        power: None,
+        custom: config
+            .fields
+            .iter()
+            .filter_map(|field| config.custom_field(field))
+            .filter_map(|custom| Some((custom.name.clone(), guarded(None, || run_custom(&custom.command))?)))
+            .collect(),
+        file_fields: config
+            .fields
+            .iter()
+            .filter_map(|field| config.file_field(field))
+            .map(|file| (file.name.clone(), guarded(None, || first_line(&file.path))))
+            .collect(),
+    };
+    refresh_volatile(&mut info, config);
+    if config.anonymize {
+        anonymize(&mut info);
+    }
+    info
+}
+
+/// `--anonymize`: blanks out what identifies the machine or its owner, for
+/// screenshots and bug reports.
+fn anonymize(info: &mut SystemInfo) {
+    const REDACTED: &str = "[redacted]";
+    info.user = REDACTED.to_string();
+    info.host = REDACTED.to_string();
+    for value in [info.network.as_mut().and_then(|n| n.ssid.as_mut()), info.gateway.as_mut()].into_iter().flatten() {
+        *value = REDACTED.to_string();
+    }
+    for server in &mut info.dns {
+        *server = REDACTED.to_string();
+    }
+}
+
+/// Re-reads the fields that change while the machine runs. `collect` fills
+/// them through here too, so long-lived modes can refresh one `SystemInfo`.
+fn refresh_volatile(info: &mut SystemInfo, config: &Config) {
+    (info.uptime, info.idle) = guarded(None, get_uptime).map_or((None, None), |(up, idle)| (Some(up), idle));
+    info.memory = guarded(None, get_memory);
+    if config.fast {
+        return;
+    }
+    info.swap_devices = if config.swap_detail { guarded(Vec::new(), get_swap_devices) } else { Vec::new() };
+    // /proc/swaps is exact, so prefer its sum when we read it anyway.
+    info.swap = if info.swap_devices.is_empty() {
+        guarded(None, get_swap)
+    } else {
+        Some(info.swap_devices.iter().fold(Usage { used: 0, total: 0 }, |sum, d| Usage {
+            used: sum.used.saturating_add(d.usage.used),
+            total: sum.total.saturating_add(d.usage.total),
+        }))
+    };
+    info.storage = get_storages(config);
+    info.loadavg = guarded(None, get_loadavg);
+    if config.fields.iter().any(|f| f == "pressure") {
+        info.pressure = guarded(None, get_pressure);
+    }
+    if config.fields.iter().any(|f| f == "cpu_power") {
+        info.cpu_power = guarded(None, get_cpu_power);
+    }
+    // Unplugging the charger is exactly what a watch would want to see.
+    if config.fields.iter().any(|f| f == "power") {
+        info.power = guarded(None, || get_power_in(Path::new("/")));
+    }
+}
+
+/// Set when `guarded` caught a panic, for `--strict`.
+static COLLECTOR_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Runs one collector, falling back to `fallback` if it panics.
+fn guarded<T>(fallback: T, collector: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(collector)).unwrap_or_else(|_| {
+        COLLECTOR_FAILED.store(true, Ordering::Relaxed);
+        fallback
+    })
 }
 
 fn get_username() -> String {
     std::env::var("USER").unwrap_or_else(|_|"unknown".into())
 }
 
-fn get_hostname() -> String {
-    fs::read_to_string("/etc/hostname")
-        .unwrap_or_else(|_| "unknown".into())
-        .trim()
-        .to_string()
+/// The host name from `/etc/hostname`, then Android's properties and
+/// `uname -n`; without `commands`, as under `--fast`, nothing is run.
+fn get_hostname(style: HostnameStyle, commands: bool) -> String {
+    let name = fs::read_to_string("/etc/hostname")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .or_else(|| if is_android() { getprop("net.hostname", commands) } else { None })
+        .or_else(|| if commands { uname_nodename() } else { None })
+        .unwrap_or_else(|| "unknown".into());
+    match style {
+        HostnameStyle::Full => name,
+        HostnameStyle::Short => short_hostname(&name).to_string(),
+    }
+}
+
+/// The first label of `name` (`box` for `box.lan.example.com`). A name that
+/// is really an IP address is kept whole, since its dots aren't labels.
+fn short_hostname(name: &str) -> &str {
+    if name.parse::<std::net::IpAddr>().is_ok() {
+        return name;
+    }
+    match name.split_once('.') {
+        Some((label, _)) if !label.is_empty() => label,
+        _ => name,
+    }
+}
+
+fn uname_nodename() -> Option<String> {
+    let output = Command::new("uname").arg("-n").output().ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Termux binaries are often built for (and report) plain linux, so the
+/// target check alone misses them; look for the Android system partition too.
+fn is_android() -> bool {
+    cfg!(target_os = "android")
+        || Path::new("/system/build.prop").exists()
+        || Path::new("/system/bin/getprop").exists()
+}
+
+/// Reads an Android system property, via `getprop` or straight from
+/// `/system/build.prop` when the binary isn't reachable or, without
+/// `commands`, not to be run.
+fn getprop(name: &str, commands: bool) -> Option<String> {
+    let from_cmd = commands
+        .then(|| Command::new("getprop").arg(name).output().ok())
+        .flatten()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty());
+    if from_cmd.is_some() {
+        return from_cmd;
+    }
+
+    let content = fs::read_to_string("/system/build.prop").ok()?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn get_android_os(commands: bool) -> String {
+    let version = getprop("ro.build.version.release", commands);
+    let model = getprop("ro.product.model", commands);
+    match (version, model) {
+        (Some(v), Some(m)) => format!("Android {} ({})", v, m),
+        (Some(v), None) => format!("Android {}", v),
+        (None, Some(m)) => format!("Android ({})", m),
+        (None, None) => "Android".into(),
+    }
 }
 
-fn get_os() -> String {
-    let content = fs::read_to_string("/etc/os-release")
-        .unwrap_or_else(|_| return "unknow".into());
+fn get_os(commands: bool) -> String {
+    if is_android() {
+        return get_android_os(commands);
+    }
+    get_os_in(Path::new("/"))
+}
+
+/// `PRETTY_NAME` from os-release under `root`. The spec puts the file in
+/// `/etc`, falling back to `/usr/lib` where `/etc` is kept minimal.
+fn get_os_in(root: &Path) -> String {
+    let content = fs::read_to_string(root.join("etc/os-release"))
+        .or_else(|_| fs::read_to_string(root.join("usr/lib/os-release")))
+        .unwrap_or_else(|_| "unknown".into());
         for line in content.lines() {
             if line.starts_with("PRETTY_NAME=") {
-                return line
+                let name = line
                     .replace ("PRETTY_NAME=", "")
                     .replace ('"', "");
+                if !name.trim().is_empty() {
+                    return name;
+                }
             }
         }
     "unknown".into()
 }
 
+/// The WSL generation, from the Microsoft kernel's release string:
+/// `5.15.90.1-microsoft-standard-WSL2`, or `4.4.0-19041-Microsoft` on WSL1.
+fn wsl_version_in(root: &Path) -> Option<u8> {
+    let release = fs::read_to_string(root.join("proc/sys/kernel/osrelease")).ok()?.to_lowercase();
+    if !release.contains("microsoft") {
+        return None;
+    }
+    Some(if release.contains("wsl2") { 2 } else { 1 })
+}
+
+/// Whether `root` is a different directory from PID 1's root, i.e. we have
+/// been chrooted. `None` when `/proc/1/root` can't be read, which needs
+/// root or the same user as init.
+fn chroot_in(root: &Path) -> Option<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let ours = fs::metadata(root).ok()?;
+    let init = fs::metadata(root.join("proc/1/root")).ok()?;
+    Some((ours.dev(), ours.ino()) != (init.dev(), init.ino()))
+}
+
+/// Vendors leave these in DMI fields they didn't fill in.
+const DMI_PLACEHOLDERS: &[&str] = &[
+    "To be filled by O.E.M.",
+    "System Product Name",
+    "System Version",
+    "Default string",
+    "Not Applicable",
+    "None",
+];
+
+fn get_model() -> Option<String> {
+    let read = |name: &str| {
+        fs::read_to_string(Path::new("/sys/class/dmi/id").join(name))
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty() && !DMI_PLACEHOLDERS.contains(&s.as_str()))
+    };
+    let name = read("product_name")?;
+    // Lenovo keeps the marketing name in the version and a part number in
+    // the name.
+    Some(match read("product_version") {
+        Some(version) if !name.contains(&version) => format!("{} {}", name, version),
+        _ => name,
+    })
+}
+
 fn detect_init() -> String {
-    let comm = fs::read_to_string("/proc/1/comm")
+    // macOS has no /proc to look in, and PID 1 is always launchd.
+    if cfg!(target_os = "macos") {
+        return "launchd".into();
+    }
+    detect_init_in(Path::new("/"))
+}
+
+/// `detect_init` against a filesystem rooted at `root`, so tests can hand
+/// it a fake `/proc` and `/run`.
+fn detect_init_in(root: &Path) -> String {
+    let comm = fs::read_to_string(root.join("proc/1/comm"))
         .unwrap_or_default()
         .trim()
         .to_string();
   
-    let exe = fs::read_link("/proc/1/exe")
+    let exe = fs::read_link(root.join("proc/1/exe"))
         .ok()
         .and_then(|p| p.to_str().map(|s| s.to_string()))
         .unwrap_or_default();
     
     match comm.as_str() {
         "systemd" => "systemd".into(),
-        "runit" | "runsvinit" => "runit".into(),
-        "s6-svscan" => "s6".into(),
+        "runit" | "runsvinit" | "runit-init" => "runit".into(),
+        "dinit" => "dinit".into(),
+        "openrc-init" => "openrc".into(),
+        "launchd" => "launchd".into(),
+        // s6-linux-init execs into s6-svscan as PID 1; s6-rc, when used,
+        // keeps its live state under /run/s6-rc.
+        "s6-svscan" => {
+            if root.join("run/s6-rc").exists() {
+                "s6-rc".into()
+            } else if root.join("etc/s6-linux-init").exists() {
+                "s6-linux-init".into()
+            } else {
+                "s6".into()
+            }
+        }
         "init" => {
-            if exe.contains("openrc") {
+            if exe == "/init" && wsl_version_in(root).is_some() {
+                // WSL's own stub, unless systemd is enabled in wsl.conf.
+                "none (WSL)".into()
+            } else if exe.contains("openrc") {
                 "openrc".into()
             } else {
                 "sysvinit".into()
             }
         }
         _ => {
-            if Path::new("/run/systemd/systemd").exists() {
+            if root.join("run/systemd/systemd").exists() {
                 "systemd (fallback)".into()
             } else {
                 format!("unknown ({})", comm)
@@ -82,7 +541,569 @@ fn detect_init() -> String {
 }
 
 
+fn get_services() -> Option<Services> {
+    let count = |state: &str| -> Option<usize> {
+        let output = Command::new("systemctl")
+            .args(["list-units", "--no-legend", "--plain", "--all", "--state", state])
+            .output()
+            .ok()?;
+        output.status.success().then(|| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .count()
+        })
+    };
+    Some(Services { running: count("running")?, failed: count("failed")? })
+}
+
+/// Counts pending package updates with the distro's own checker. This can
+/// take seconds and hit the network, hence opt-in and cached.
+fn get_updates() -> Option<usize> {
+    let count_lines = |text: &[u8], keep: &dyn Fn(&str) -> bool| {
+        String::from_utf8_lossy(text).lines().filter(|l| keep(l)).count()
+    };
+
+    if command_exists("checkupdates") {
+        let output = Command::new("checkupdates").output().ok()?;
+        // Exit status 2 means "no updates", anything else non-zero failed.
+        return match output.status.code() {
+            Some(0) => Some(count_lines(&output.stdout, &|l| !l.trim().is_empty())),
+            Some(2) => Some(0),
+            _ => None,
+        };
+    }
+    if command_exists("apt") {
+        let output = Command::new("apt").args(["list", "--upgradable"]).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        return Some(count_lines(&output.stdout, &|l| l.contains('/') && !l.starts_with("Listing")));
+    }
+    if command_exists("dnf") {
+        let output = Command::new("dnf").args(["check-update", "-q"]).output().ok()?;
+        // dnf exits 100 when updates exist; the list ends where obsoletes begin.
+        return match output.status.code() {
+            Some(0) => Some(0),
+            Some(100) => {
+                let text = String::from_utf8_lossy(&output.stdout);
+                let updates = text.split("Obsoleting Packages").next().unwrap_or("");
+                Some(count_lines(updates.as_bytes(), &|l| !l.trim().is_empty()))
+            }
+            _ => None,
+        };
+    }
+    None
+}
+
+/// Installed package counts per source, skipping sources with none.
+/// Native counts come from the package database files where we can read
+/// them, which is much faster than asking the package manager.
+fn get_packages(config: &Config) -> Vec<PackageCount> {
+    let toggles = &config.packages;
+    let mut counts = Vec::new();
+    if toggles.native {
+        counts.extend(get_native_packages());
+    }
+    if toggles.flatpak
+        && let Some(count) = count_output_lines("flatpak", &["list"])
+    {
+        counts.push(PackageCount { source: "flatpak".into(), count });
+    }
+    if toggles.snap
+        && let Some(count) = get_snap_packages()
+    {
+        counts.push(PackageCount { source: "snap".into(), count });
+    }
+    // Foreign packages are mostly AUR builds. They're also in pacman's count.
+    if toggles.aur
+        && let Some(count) = count_output_lines("pacman", &["-Qqm"])
+    {
+        counts.push(PackageCount { source: "aur".into(), count });
+    }
+    counts.retain(|c| c.count > 0);
+    counts
+}
+
+/// Counts the distro's packages. Reading the package database directly
+/// takes a millisecond where the package manager can take hundreds, so
+/// the package managers are only asked when no database is readable.
+fn get_native_packages() -> Option<PackageCount> {
+    let found = |source: &str, count: usize| Some(PackageCount { source: source.into(), count });
+
+    if let Ok(entries) = fs::read_dir("/var/lib/pacman/local") {
+        // One directory per package, next to an ALPM_DB_VERSION file.
+        return found("pacman", entries.flatten().filter(|e| e.path().is_dir()).count());
+    }
+    if let Ok(status) = fs::read_to_string("/var/lib/dpkg/status") {
+        return found("dpkg", count_dpkg_installed(&status));
+    }
+    if let Ok(installed) = fs::read_to_string("/lib/apk/db/installed") {
+        return found("apk", count_apk_installed(&installed));
+    }
+
+    if let Some(count) = count_output_lines("pacman", &["-Qq"]) {
+        return found("pacman", count);
+    }
+    if command_exists("dpkg-query") {
+        let output = Command::new("dpkg-query").args(["-f", "${Status}\n", "-W"]).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        return found("dpkg", text.lines().filter(|state| dpkg_installed(state)).count());
+    }
+    if let Some(count) = count_output_lines("apk", &["info"]) {
+        return found("apk", count);
+    }
+    // The rpm database is SQLite (or Berkeley DB), so there's no cheap
+    // direct read.
+    if let Some(count) = count_output_lines("rpm", &["-qa"]) {
+        return found("rpm", count);
+    }
+    None
+}
+
+/// Packages in a dpkg `status` file that are currently installed.
+fn count_dpkg_installed(status: &str) -> usize {
+    status
+        .split("\n\n")
+        .filter(|stanza| {
+            stanza
+                .lines()
+                .find_map(|line| line.strip_prefix("Status: "))
+                .is_some_and(dpkg_installed)
+        })
+        .count()
+}
+
+/// Whether a dpkg status (`install ok installed`) means installed, whatever
+/// the selection (`install`, `hold`, ...). Removed packages stay behind as
+/// `config-files` until purged.
+fn dpkg_installed(status: &str) -> bool {
+    status.split_whitespace().nth(2) == Some("installed")
+}
+
+/// Packages in apk's `installed` database: one `P:` (name) line each.
+fn count_apk_installed(installed: &str) -> usize {
+    installed.lines().filter(|line| line.starts_with("P:")).count()
+}
+
+/// Non-empty lines `program` prints, or `None` if it's missing or fails.
+fn count_output_lines(program: &str, args: &[&str]) -> Option<usize> {
+    if !command_exists(program) {
+        return None;
+    }
+    let output = Command::new(program).args(args).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    output.status.success().then(|| text.lines().filter(|l| !l.trim().is_empty()).count())
+}
+
+/// Installed snaps. snapd keeps a `<name>_<revision>.snap` per revision,
+/// so count distinct names.
+fn get_snap_packages() -> Option<usize> {
+    let mut names: Vec<String> = fs::read_dir("/var/lib/snapd/snaps")
+        .ok()?
+        .flatten()
+        .filter_map(|e| {
+            let file = e.file_name().to_string_lossy().into_owned();
+            let stem = file.strip_suffix(".snap")?;
+            Some(stem.rsplit_once('_').map_or(stem, |(name, _)| name).to_string())
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    Some(names.len())
+}
+
+/// Runs a `[[custom]]` command through `sh -c`, keeping its trimmed stdout
+/// when it succeeds and prints something.
+fn run_custom(command: &str) -> Option<String> {
+    let output = Command::new("sh").args(["-c", command]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// The first non-blank line of `path`, trimmed, for `[[file]]` fields.
+fn first_line(path: &Path) -> Option<String> {
+    let text = fs::read_to_string(path).ok()?;
+    text.lines().map(str::trim).find(|line| !line.is_empty()).map(String::from)
+}
+
+/// Whether `name` is an executable somewhere on `$PATH`.
+fn command_exists(name: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| dir.join(name).is_file())
+    })
+}
+
+/// The branch of the git work tree containing the current directory, found
+/// by walking up to a `.git`. The dirty check runs `git status`, so it can
+/// be turned off with `git_dirty = false`.
+fn get_git(check_dirty: bool) -> Option<GitRepo> {
+    let cwd = std::env::current_dir().ok()?;
+    let (root, git) = cwd.ancestors().find_map(|dir| {
+        let git = dir.join(".git");
+        git.exists().then(|| (dir.to_path_buf(), git))
+    })?;
+
+    // Worktrees and submodules have a `.git` file pointing at the real dir.
+    let git_dir = if git.is_file() {
+        let content = fs::read_to_string(&git).ok()?;
+        root.join(content.trim().strip_prefix("gitdir:")?.trim())
+    } else {
+        git
+    };
+
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let branch = match head.strip_prefix("ref: ") {
+        Some(reference) => reference.strip_prefix("refs/heads/").unwrap_or(reference).to_string(),
+        // Detached HEAD: show the short commit instead.
+        None => head.chars().take(7).collect(),
+    };
+
+    let dirty = check_dirty
+        .then(|| {
+            let output = Command::new("git")
+                .args(["status", "--porcelain"])
+                .current_dir(&root)
+                .output()
+                .ok()?;
+            output.status.success().then_some(!output.stdout.is_empty())
+        })
+        .flatten();
+
+    Some(GitRepo { branch, dirty })
+}
+
+fn get_fans() -> Vec<Fan> {
+    let fans: Vec<_> = hwmon::chips()
+        .into_iter()
+        .flat_map(|chip| {
+            chip.sensors("fan").into_iter().map(move |fan| {
+                // Unlabelled fans are only numbered; prefix the driver name so
+                // fans on different chips stay apart.
+                let label = if fan.labelled || chip.name.is_empty() {
+                    fan.label
+                } else {
+                    format!("{} {}", chip.name, fan.label)
+                };
+                (label, fan.labelled, fan.input.max(0) as u64)
+            })
+        })
+        .collect();
+    // A single fan without a driver label needs no label at all.
+    let show_labels = fans.len() > 1 || fans.iter().any(|(_, labelled, _)| *labelled);
+    fans.into_iter()
+        .map(|(label, _, rpm)| Fan { label: show_labels.then_some(label), rpm })
+        .collect()
+}
+
+/// The running Wayland compositor and its version, asked over its own IPC.
+/// Each compositor exports a socket variable into the sessions it runs.
+fn get_compositor() -> Option<String> {
+    let env = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+    let run = |program: &str, args: &[&str]| -> Option<String> {
+        let output = Command::new(program).args(args).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    if env("HYPRLAND_INSTANCE_SIGNATURE") {
+        // `"tag": "v0.41.2-..."` in `hyprctl version -j`.
+        let version = run("hyprctl", &["version", "-j"]).and_then(|json| {
+            let rest = json.split_once("\"tag\"")?.1;
+            let tag = rest.split('"').nth(1)?;
+            Some(tag.trim_start_matches('v').split('-').next()?.to_string())
+        });
+        return Some(match version {
+            Some(version) => format!("Hyprland {}", version),
+            None => "Hyprland".to_string(),
+        });
+    }
+    if env("SWAYSOCK") {
+        // `sway version 1.9`
+        let version = run("swaymsg", &["-t", "get_version"])
+            .and_then(|text| Some(text.trim().strip_prefix("sway version ")?.to_string()));
+        return Some(match version {
+            Some(version) => format!("sway {}", version),
+            None => "sway".to_string(),
+        });
+    }
+    None
+}
+
+/// The active mandatory access control frameworks and their modes, e.g.
+/// `SELinux (Enforcing)`. The sysfs files are world-readable; the tools are
+/// only asked when they aren't there.
+fn get_security() -> Option<String> {
+    let mut active = Vec::new();
+
+    let selinux = match fs::read_to_string("/sys/fs/selinux/enforce").map(|s| s.trim().to_string()).as_deref() {
+        Ok("1") => Some("Enforcing".to_string()),
+        Ok("0") => Some("Permissive".to_string()),
+        _ if command_exists("getenforce") => Command::new("getenforce")
+            .output()
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .filter(|mode| mode == "Enforcing" || mode == "Permissive"),
+        _ => None,
+    };
+    if let Some(mode) = selinux {
+        active.push(format!("SELinux ({})", mode));
+    }
+
+    let apparmor = match fs::read_to_string("/sys/module/apparmor/parameters/enabled") {
+        Ok(enabled) => enabled.trim() == "Y",
+        // `aa-status --enabled` answers through its exit status alone.
+        Err(_) => command_exists("aa-status")
+            && Command::new("aa-status").arg("--enabled").status().is_ok_and(|s| s.success()),
+    };
+    if apparmor {
+        active.push("AppArmor (enabled)".to_string());
+    }
+
+    (!active.is_empty()).then(|| active.join(", "))
+}
+
+/// The I/O scheduler of the disk behind `/`: its device number leads to the
+/// partition under /sys/dev/block, the partition to its disk, and device
+/// mapper volumes (LVM, LUKS) to the first disk beneath them.
+fn get_scheduler() -> Option<Scheduler> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = fs::metadata("/").ok()?.dev();
+    let (major, minor) = (libc::major(dev), libc::minor(dev));
+    let mut block = fs::canonicalize(format!("/sys/dev/block/{}:{}", major, minor)).ok()?;
+    // A dm volume's own queue is "none"; its slaves hold the real disk.
+    for _ in 0..4 {
+        let slave = fs::read_dir(block.join("slaves")).ok().and_then(|mut slaves| slaves.next()?.ok());
+        let Some(slave) = slave else {
+            break;
+        };
+        block = fs::canonicalize(slave.path()).ok()?;
+    }
+    if block.join("partition").exists() {
+        block = block.parent()?.to_path_buf();
+    }
+    let device = block.file_name()?.to_string_lossy().into_owned();
+    let (active, available) = parse_scheduler(&fs::read_to_string(block.join("queue/scheduler")).ok()?)?;
+    Some(Scheduler { device, active, available })
+}
+
+/// `mq-deadline kyber [bfq] none`: the bracketed entry is the active one.
+/// Queues with no choice list just `none`, unbracketed.
+fn parse_scheduler(text: &str) -> Option<(String, Vec<String>)> {
+    let mut active = None;
+    let mut available = Vec::new();
+    for name in text.split_whitespace() {
+        match name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+            Some(name) => {
+                active = Some(name.to_string());
+                available.push(name.to_string());
+            }
+            None => available.push(name.to_string()),
+        }
+    }
+    let active = match (active, available.as_slice()) {
+        (Some(active), _) => active,
+        (None, [only]) => only.clone(),
+        _ => return None,
+    };
+    Some((active, available))
+}
+
+/// The cpufreq governor, plus the energy performance preference where the
+/// driver has one, e.g. `powersave (balance_power)`. Either reads `mixed`
+/// when the cores disagree.
+fn get_governor() -> Option<String> {
+    let mut policies: Vec<_> = fs::read_dir("/sys/devices/system/cpu/cpufreq")
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("policy")))
+        .collect();
+    policies.sort();
+
+    let common = |file: &str| -> Option<String> {
+        let mut values: Vec<String> = policies
+            .iter()
+            .filter_map(|p| fs::read_to_string(p.join(file)).ok())
+            .map(|v| v.trim().to_string())
+            .collect();
+        values.dedup();
+        match values.as_slice() {
+            [] => None,
+            [one] => Some(one.clone()),
+            _ => Some("mixed".to_string()),
+        }
+    };
+
+    let governor = common("scaling_governor")?;
+    Some(match common("energy_performance_preference") {
+        Some(epp) => format!("{} ({})", governor, epp),
+        None => governor,
+    })
+}
+
+/// The kernel command line, keeping only parameters that start with one of
+/// `filter`'s prefixes. An empty filter keeps the whole line.
+fn get_cmdline(filter: &[String]) -> Option<String> {
+    let line = fs::read_to_string("/proc/cmdline").ok()?;
+    let params: Vec<&str> = line
+        .split_whitespace()
+        .filter(|param| filter.is_empty() || filter.iter().any(|prefix| param.starts_with(prefix.as_str())))
+        .collect();
+    (!params.is_empty()).then(|| params.join(" "))
+}
+
+/// The IPv4 default route with the lowest metric, as its interface and
+/// gateway.
+fn default_route() -> Option<(String, Ipv4Addr)> {
+    parse_default_route(&fs::read_to_string("/proc/net/route").ok()?)
+}
+
+fn parse_default_route(routes: &str) -> Option<(String, Ipv4Addr)> {
+    routes
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            // Iface Destination Gateway Flags RefCnt Use Metric ...
+            if cols.len() <= 6 || cols[1] != "00000000" {
+                return None;
+            }
+            // The kernel prints the address as a native-endian u32, so
+            // 192.168.1.1 reads `0101A8C0` on x86.
+            let gateway = Ipv4Addr::from(u32::from_str_radix(cols[2], 16).ok()?.to_ne_bytes());
+            Some((cols[6].parse::<u64>().unwrap_or(u64::MAX), cols[0], gateway))
+        })
+        .min()
+        .map(|(_, interface, gateway)| (interface.to_string(), gateway))
+}
+
+/// systemd-resolved's local stub addresses: the stub proper, and the
+/// proxy one that skips local synthesis.
+const RESOLVED_STUBS: [&str; 2] = ["127.0.0.53", "127.0.0.54"];
+
+/// The configured DNS servers. Under systemd-resolved, resolv.conf only
+/// names its local stub, so the servers behind it come from `resolvectl`.
+fn get_dns() -> Vec<String> {
+    let servers = parse_resolv_conf(&fs::read_to_string("/etc/resolv.conf").unwrap_or_default());
+    if !is_resolved_stub(&servers) {
+        return servers;
+    }
+    let upstream = Command::new("resolvectl")
+        .args(["--no-pager", "dns"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| parse_resolvectl_dns(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default();
+    if upstream.is_empty() { servers } else { upstream }
+}
+
+fn is_resolved_stub(servers: &[String]) -> bool {
+    !servers.is_empty() && servers.iter().all(|s| RESOLVED_STUBS.contains(&s.as_str()))
+}
+
+fn parse_resolv_conf(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `resolvectl dns` prints `Global: 1.1.1.1` and `Link 2 (wlan0): ...`
+/// lines; servers may carry a `#server-name` for DNS over TLS, or an
+/// `%interface`. Each address is listed once, in order.
+fn parse_resolvectl_dns(text: &str) -> Vec<String> {
+    let mut servers: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let Some((_, list)) = line.split_once(": ") else {
+            continue;
+        };
+        for server in list.split_whitespace() {
+            let address = server.split(['#', '%']).next().unwrap_or(server);
+            if !servers.iter().any(|s| s == address) {
+                servers.push(address.to_string());
+            }
+        }
+    }
+    servers
+}
+
+/// The interface the default route goes through.
+fn get_network() -> Option<Network> {
+    let (interface, _) = default_route()?;
+
+    let sys = Path::new("/sys/class/net").join(&interface);
+    if !sys.join("wireless").exists() && !sys.join("phy80211").exists() {
+        let kind = if sys.join("device").exists() { "wired" } else { "virtual" };
+        return Some(Network { interface, kind: kind.to_string(), ssid: None, signal_dbm: None });
+    }
+
+    let ssid = Command::new("iwgetid")
+        .args(["-r", &interface])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .filter(|ssid| !ssid.is_empty());
+    let signal_dbm = fs::read_to_string("/proc/net/wireless").ok().and_then(|table| {
+        // Two header lines, then `wlan0: 0000   70.  -40.  -256 ...`:
+        // status, link quality, signal level, noise.
+        let line = table.lines().skip(2).find(|l| l.trim_start().starts_with(&format!("{}:", interface)))?;
+        let level: i32 = line.split_whitespace().nth(3)?.trim_end_matches('.').parse().ok()?;
+        // Some drivers report the level as an unsigned byte.
+        Some(if level > 0 { level - 256 } else { level })
+    });
+    Some(Network { interface, kind: "wifi".to_string(), ssid, signal_dbm })
+}
+
+/// `/proc/sys/fs/file-nr`: allocated handles, free ones among them (always
+/// 0 since Linux 2.6) and the limit.
+fn get_file_handles() -> Option<FileHandles> {
+    let text = fs::read_to_string("/proc/sys/fs/file-nr").ok()?;
+    let mut values = text.split_whitespace().map(|v| v.parse::<u64>().ok());
+    let allocated = values.next()??;
+    let free = values.next()??;
+    let max = values.next()??;
+    Some(FileHandles { open: allocated.saturating_sub(free), max })
+}
+
+/// Bits of entropy the kernel's pool holds. Since Linux 5.18 this is always
+/// 256, as the pool no longer runs dry.
+fn get_entropy() -> Option<u64> {
+    read_u64(Path::new("/proc/sys/kernel/random/entropy_avail"))
+}
+
+/// Loaded kernel modules. Hardened kernels may hide `/proc/modules`.
+fn get_kernel_modules(top: usize) -> Option<KernelModules> {
+    let text = fs::read_to_string("/proc/modules").ok()?;
+    // `name size refcount deps state address`
+    let mut modules: Vec<(String, u64)> = text
+        .lines()
+        .filter_map(|line| {
+            let mut cols = line.split_whitespace();
+            let name = cols.next()?;
+            Some((name.to_string(), cols.next()?.parse().ok()?))
+        })
+        .collect();
+    let count = modules.len();
+    modules.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    modules.truncate(top);
+    Some(KernelModules { count, largest: modules })
+}
+
+/// The kernel release, from /proc so `--fast` needs no `uname`.
 fn get_kernel() -> String {
+    if let Ok(release) = fs::read_to_string("/proc/sys/kernel/osrelease")
+        && !release.trim().is_empty()
+    {
+        return release.trim().to_string();
+    }
     let output = Command::new("uname")
         .arg("-r")
         .output();
@@ -94,22 +1115,72 @@ fn get_kernel() -> String {
     }
 }
 
-fn get_uptime() -> String {
-    let content = fs::read_to_string("/proc/uptime")
-        .unwrap_or_else(|_| return "unknown".into());
-   
-    let seconds: f64 = content
+/// The day the running kernel was compiled, from `/proc/version`.
+fn get_kernel_build() -> Option<String> {
+    kernel::build_date(&fs::read_to_string("/proc/version").ok()?)
+}
+
+/// Seconds since boot, and the idle percentage when it can be worked out.
+fn get_uptime() -> Option<(u64, Option<f64>)> {
+    let content = fs::read_to_string("/proc/uptime").ok()?;
+    // SAFETY: sysconf only reads a system value.
+    let cpus = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    parse_uptime(&content, u32::try_from(cpus).unwrap_or(0))
+}
+
+/// `/proc/uptime` is `<uptime> <idle>` in seconds, where idle is summed
+/// over all `cpus`. A corrupt uptime is `None`; a corrupt idle time, or no
+/// CPU count, only loses the idle percentage.
+fn parse_uptime(content: &str, cpus: u32) -> Option<(u64, Option<f64>)> {
+    let mut values = content
         .split_whitespace()
-        .next()
-        .unwrap_or("0")
+        .map(|v| v.parse::<f64>().ok().filter(|s| s.is_finite() && *s >= 0.0));
+    let uptime = values.next()??;
+    let idle = values.next().flatten().and_then(|idle| {
+        let total = uptime * cpus as f64;
+        (total > 0.0).then(|| (idle / total * 100.0).clamp(0.0, 100.0))
+    });
+    // `as` saturates, so an absurd uptime can't wrap.
+    Some((uptime as u64, idle))
+}
+
+fn get_boot_time() -> Option<u64> {
+    let content = fs::read_to_string("/proc/stat").ok()?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
         .parse()
-        .unwrap_or(0.0);
-    
-    let minutes = (seconds / 60.0) as u64;
-    let hours = minutes / 60;
-    let mins = minutes %60 ;
-    
-    format!("{}h {}m", hours, mins)
+        .ok()
+}
+
+/// `None` on kernels built without PSI, where `/proc/pressure` is missing.
+fn get_pressure() -> Option<Pressure> {
+    let read = |name: &str| fs::read_to_string(Path::new("/proc/pressure").join(name)).ok();
+    let pressure = Pressure {
+        cpu: read("cpu").as_deref().and_then(psi_some_avg10),
+        memory: read("memory").as_deref().and_then(psi_some_avg10),
+    };
+    (pressure.cpu.is_some() || pressure.memory.is_some()).then_some(pressure)
+}
+
+/// `avg10` from the `some` line of a PSI file:
+/// `some avg10=0.30 avg60=0.12 avg300=0.05 total=123456`.
+fn psi_some_avg10(text: &str) -> Option<f64> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("some "))?
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("avg10="))?
+        .parse()
+        .ok()
+}
+
+fn get_loadavg() -> Option<[f64; 3]> {
+    let content = fs::read_to_string("/proc/loadavg").ok()?;
+    let mut values = content
+        .split_whitespace()
+        .map(|v| v.parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0));
+    Some([values.next()??, values.next()??, values.next()??])
 }
 
 fn get_shell() -> String {
@@ -123,59 +1194,974 @@ fn get_shell() -> String {
     .unwrap_or("unknown".into())
 }
 
-fn get_memory() -> String {
-    let content = fs::read_to_string("/proc/meminfo")
-        .unwrap_or_else(|_| return "unknown".into());
-
-    let mut total = 0;
-    let mut available = 0;
-
-    for line in content.lines() {
-        if line.starts_with("MemTotal:") {
-            total = extract_kb(line);
-        } else if line.starts_with("MemAvailable: ") {
-            available = extract_kb(line);
-        }
-    }
+const DISPLAY_MANAGERS: &[&str] = &[
+    "gdm", "gdm3", "sddm", "lightdm", "ly", "lxdm", "xdm", "greetd", "slim", "entrance", "emptty",
+];
 
-    if total == 0 {
-        return "unknown".into();
+/// Reports the login manager, or `None` when the system boots to a TTY.
+fn get_display_manager() -> Option<String> {
+    if let Ok(target) = fs::read_link("/etc/systemd/system/display-manager.service")
+        && let Some(stem) = target.file_stem()
+    {
+        return Some(stem.to_string_lossy().into_owned());
     }
 
-    let used = total - available;
-    format!(
-        "{:.1} G / {:.1} G",
-        kb_to_gib(used),
-        kb_to_gib(total)
-    )
+    process_names()
+        .into_iter()
+        .find(|name| DISPLAY_MANAGERS.contains(&name.as_str()))
 }
 
-fn kb_to_gib(kb: u64) -> f64 {
-    kb as f64 / 1024.0 / 1024.0
+/// Notification daemons by process name, which the kernel cuts to 15
+/// characters, and the name to show.
+const NOTIFICATION_DAEMONS: &[(&str, &str)] = &[
+    ("dunst", "dunst"),
+    ("mako", "mako"),
+    ("swaync", "swaync"),
+    ("fnott", "fnott"),
+    ("wired", "wired"),
+    ("tiramisu", "tiramisu"),
+    ("xfce4-notifyd", "xfce4-notifyd"),
+    ("notify-osd", "notify-osd"),
+    ("notification-da", "notification-daemon"),
+    ("deadd-notificat", "deadd-notification-center"),
+    ("linux_notificat", "linux_notification_center"),
+    ("mate-notificati", "mate-notification-daemon"),
+    ("lxqt-notificati", "lxqt-notificationd"),
+];
+
+fn get_notification_daemon() -> Option<String> {
+    let names = process_names();
+    NOTIFICATION_DAEMONS
+        .iter()
+        .find(|(comm, _)| names.iter().any(|name| name == comm))
+        .map(|(_, daemon)| daemon.to_string())
 }
 
-fn extract_kb(line: &str) -> u64 {
-    line.split_whitespace()
-        .nth(1)
-        .unwrap_or("0")
-        .parse()
-        .unwrap_or(0)
+/// Names (`/proc/<pid>/comm`) of every running process we can see.
+fn process_names() -> Vec<String> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()))
+        .filter_map(|e| fs::read_to_string(e.path().join("comm")).ok())
+        .map(|comm| comm.trim().to_string())
+        .collect()
 }
 
-fn get_swap() -> String {
-    let mut sys = System::new_all();
-    sys.refresh_memory();
-    
-    let total = sys.total_swap() as f64 / 1_000_000_000.0;
-    let used = sys.used_swap()as f64 / 1_000_000_000.0;
+const SHELLS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "dash", "ksh", "mksh", "tcsh", "csh", "nu", "elvish", "xonsh",
+];
 
-    format!("{:.1} G / {:.1} G", used, total)
+/// The multiplexer rfetch runs in, from the variable each one sets for
+/// its panes. Inside one, the terminal field finds the multiplexer's
+/// server rather than the emulator the client is attached from.
+fn get_multiplexer() -> Option<String> {
+    let set = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+    [("TMUX", "tmux"), ("STY", "screen"), ("ZELLIJ", "zellij")]
+        .into_iter()
+        .find(|(var, _)| set(var))
+        .map(|(_, name)| name.to_string())
 }
 
-fn get_storage(path: &str) -> String {
-    let disks=Disks::new_with_refreshed_list();
+/// Lock key state from the keyboard LEDs under `root`'s
+/// /sys/class/leds, named like `input3::capslock`. `None` when there are
+/// none, as on headless machines and many laptops.
+fn get_locks_in(root: &Path) -> Option<Locks> {
+    let mut locks = None;
+    for entry in fs::read_dir(root.join("sys/class/leds")).ok()?.flatten() {
+        let name = entry.file_name();
+        let Some((_, led)) = name.to_str().and_then(|name| name.split_once("::")) else {
+            continue;
+        };
+        // Other LEDs (power, Wi-Fi, mute) don't make a keyboard.
+        let locks = match led {
+            "capslock" | "numlock" | "scrolllock" => locks.get_or_insert_with(Locks::default),
+            _ => continue,
+        };
+        let lit = read_u64(&entry.path().join("brightness")).is_some_and(|b| b > 0);
+        match led {
+            "capslock" => locks.caps |= lit,
+            "numlock" => locks.num |= lit,
+            _ => locks.scroll |= lit,
+        }
+    }
+    locks
+}
+
+/// Whether the machine runs on mains or battery. Adapters go by AC, ACAD,
+/// ADP1, axp20x-ac and more, so any `Mains` or `USB` supply that is online
+/// means AC. A machine with no battery of its own, like a desktop, is on
+/// AC too; batteries of wireless mice and the like have a `Device` scope.
+fn get_power_in(root: &Path) -> Option<PowerSource> {
+    let mut battery = false;
+    for entry in fs::read_dir(root.join("sys/class/power_supply")).ok()?.flatten() {
+        let path = entry.path();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+        let scope = fs::read_to_string(path.join("scope")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" | "USB" if read_u64(&path.join("online")) == Some(1) => return Some(PowerSource::Ac),
+            "Battery" if scope.trim() != "Device" => battery = true,
+            _ => {}
+        }
+    }
+    Some(if battery { PowerSource::Battery } else { PowerSource::Ac })
+}
+
+/// Process names that sit between the terminal and us without being a
+/// terminal themselves.
+const WRAPPERS: &[&str] = &["sudo", "su", "doas", "rfetch", "cargo", "script", "env"];
+
+/// Finds the terminal emulator by walking up the process tree past shells
+/// and wrappers. `None` on a bare TTY or over SSH.
+fn get_terminal() -> Option<String> {
+    if let Ok(program) = std::env::var("TERM_PROGRAM")
+        && !program.is_empty()
+    {
+        return Some(program);
+    }
+
+    let mut pid = parent_pid(std::process::id())?;
+    while pid > 1 {
+        let name = fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+        let name = name.trim();
+        if !SHELLS.contains(&name) && !WRAPPERS.contains(&name) {
+            return match name {
+                "login" | "sshd" | "agetty" | "systemd" | "init" => None,
+                "gnome-terminal-" => Some("gnome-terminal".into()),
+                "footclient" => Some("foot".into()),
+                "tmux: server" => Some("tmux".into()),
+                other => Some(other.to_string()),
+            };
+        }
+        pid = parent_pid(pid)?;
+    }
+    None
+}
+
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // comm may contain spaces and parens, so the fields start after the last ')'.
+    let after = &stat[stat.rfind(')')? + 1..];
+    after.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Reads the configured font for terminals whose config we understand.
+fn get_terminal_font(terminal: &str) -> Option<String> {
+    let dir = config::config_home()?;
+    let (family, size) = match terminal {
+        "kitty" => kitty_font(&fs::read_to_string(dir.join("kitty/kitty.conf")).ok()?)?,
+        "foot" => foot_font(&fs::read_to_string(dir.join("foot/foot.ini")).ok()?)?,
+        "alacritty" => {
+            if let Ok(content) = fs::read_to_string(dir.join("alacritty/alacritty.toml")) {
+                alacritty_toml_font(&content)?
+            } else {
+                alacritty_yaml_font(&fs::read_to_string(dir.join("alacritty/alacritty.yml")).ok()?)?
+            }
+        }
+        _ => return None,
+    };
+
+    match size {
+        Some(size) => Some(format!("{} {}", family, size.trim_end_matches(".0"))),
+        None => Some(family),
+    }
+}
+
+type FontSpec = (String, Option<String>);
+
+fn kitty_font(content: &str) -> Option<FontSpec> {
+    let mut family = None;
+    let mut size = None;
+    for line in content.lines().map(str::trim) {
+        if let Some(value) = line.strip_prefix("font_family") {
+            family = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("font_size") {
+            size = Some(value.trim().to_string());
+        }
+    }
+    Some((family.filter(|f| !f.is_empty())?, size))
+}
+
+/// foot writes fonts as `font=Family:size=11`, possibly several comma-separated.
+fn foot_font(content: &str) -> Option<FontSpec> {
+    let value = content
+        .lines()
+        .map(str::trim)
+        .find_map(|line| line.strip_prefix("font")?.trim_start().strip_prefix('='))?;
+    let first = value.split(',').next()?.trim();
+    let mut parts = first.split(':');
+    let family = parts.next()?.trim().to_string();
+    let size = parts.find_map(|p| p.trim().strip_prefix("size=").map(str::to_string));
+    (!family.is_empty()).then_some((family, size))
+}
+
+fn alacritty_toml_font(content: &str) -> Option<FontSpec> {
+    let table = toml::parse(content).ok()?;
+    let toml::Value::Table(font) = table.get("font")? else {
+        return None;
+    };
+    let family = match font.get("normal") {
+        Some(toml::Value::Table(normal)) => match normal.get("family") {
+            Some(toml::Value::String(f)) => f.clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let size = match font.get("size") {
+        Some(toml::Value::Integer(i)) => Some(i.to_string()),
+        Some(toml::Value::Float(f)) => Some(f.to_string()),
+        _ => None,
+    };
+    Some((family, size))
+}
+
+/// Just enough YAML for the legacy `alacritty.yml` layout: the first
+/// `family:` and `size:` keys nested under `font:`.
+fn alacritty_yaml_font(content: &str) -> Option<FontSpec> {
+    let mut in_font = false;
+    let mut family = None;
+    let mut size = None;
+    for line in content.lines() {
+        if !line.starts_with([' ', '\t']) {
+            in_font = line.trim_end() == "font:";
+            continue;
+        }
+        if !in_font {
+            continue;
+        }
+        let line = line.trim();
+        let unquote = |v: &str| v.trim().trim_matches(['"', '\'']).to_string();
+        if let Some(value) = line.strip_prefix("family:") {
+            family.get_or_insert_with(|| unquote(value));
+        } else if let Some(value) = line.strip_prefix("size:") {
+            size.get_or_insert_with(|| unquote(value));
+        }
+    }
+    Some((family.filter(|f| !f.is_empty())?, size))
+}
+
+/// Every PCI display controller (class `0x03xxxx`), boot GPU first.
+fn get_gpus() -> Vec<Gpu> {
+    let Ok(entries) = fs::read_dir("/sys/bus/pci/devices") else {
+        return Vec::new();
+    };
+    let ids = fs::read_to_string("/usr/share/hwdata/pci.ids")
+        .or_else(|_| fs::read_to_string("/usr/share/misc/pci.ids"))
+        .unwrap_or_default();
+
+    let mut gpus: Vec<Gpu> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let read_hex = |name: &str| {
+                let raw = fs::read_to_string(dir.join(name)).ok()?;
+                u32::from_str_radix(raw.trim().trim_start_matches("0x"), 16).ok()
+            };
+            if read_hex("class")? >> 16 != 0x03 {
+                return None;
+            }
+            let vendor = read_hex("vendor")? as u16;
+            let device = read_hex("device")? as u16;
+            let vram = read_u64(&dir.join("mem_info_vram_total"));
+            Some(Gpu {
+                name: gpu_name(&ids, vendor, device),
+                pci: entry.file_name().to_string_lossy().into_owned(),
+                kind: classify_gpu(vendor, device, vram),
+                boot_vga: read_hex("boot_vga") == Some(1),
+            })
+        })
+        .collect();
+
+    gpus.sort_by(|a, b| b.boot_vga.cmp(&a.boot_vga).then_with(|| a.pci.cmp(&b.pci)));
+    gpus
+}
+
+/// Socket and NUMA node counts, or `None` when neither could be read.
+fn get_topology() -> Option<Topology> {
+    let sockets = fs::read_to_string("/proc/cpuinfo").ok().and_then(|cpuinfo| count_sockets(&cpuinfo));
+    let numa_nodes = fs::read_dir("/sys/devices/system/node").ok().map(|entries| {
+        entries
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name();
+                let name = name.to_string_lossy();
+                name.strip_prefix("node").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .count()
+    });
+    let numa_nodes = numa_nodes.filter(|&n| n > 0);
+    (sockets.is_some() || numa_nodes.is_some()).then_some(Topology { sockets, numa_nodes })
+}
+
+/// Distinct `physical id` values, one per populated socket. ARM and most
+/// VMs' cpuinfo has none.
+fn count_sockets(cpuinfo: &str) -> Option<usize> {
+    let mut ids: Vec<&str> = cpuinfo
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "physical id").then(|| value.trim())
+        })
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    (!ids.is_empty()).then_some(ids.len())
+}
+
+/// The first CPU's vendor and, when `microarch_name` knows it, its
+/// microarchitecture, from `/proc/cpuinfo`.
+fn get_microarch() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    let value = |key: &str| {
+        cpuinfo.lines().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim())
+        })
+    };
+    let vendor_id = value("vendor_id")?;
+    let vendor = match vendor_id {
+        "GenuineIntel" => "Intel",
+        "AuthenticAMD" => "AMD",
+        "HygonGenuine" => "Hygon",
+        "CentaurHauls" => "Centaur",
+        other => other,
+    };
+    let family = value("cpu family").and_then(|f| f.parse().ok());
+    let model = value("model").and_then(|m| m.parse().ok());
+    Some(match family.zip(model).and_then(|(family, model)| microarch_name(vendor_id, family, model)) {
+        Some(name) => format!("{} ({})", vendor, name),
+        None => vendor.to_string(),
+    })
+}
+
+/// Common x86 families and models. Anything missing falls back to the
+/// bare vendor rather than a guess.
+fn microarch_name(vendor_id: &str, family: u32, model: u32) -> Option<&'static str> {
+    match (vendor_id, family) {
+        ("AuthenticAMD", 0x10) => Some("K10"),
+        ("AuthenticAMD", 0x15) => Some("Bulldozer"),
+        ("AuthenticAMD", 0x16) => Some("Jaguar"),
+        ("AuthenticAMD", 0x17) => match model {
+            0x08 | 0x18 => Some("Zen+"),
+            0x00..=0x2f => Some("Zen"),
+            0x30..=0xaf => Some("Zen 2"),
+            _ => None,
+        },
+        ("AuthenticAMD", 0x19) => match model {
+            0x40..=0x4f => Some("Zen 3+"),
+            0x00..=0x0f | 0x20..=0x5f => Some("Zen 3"),
+            0x10..=0x1f | 0x60..=0x7f | 0xa0..=0xaf => Some("Zen 4"),
+            _ => None,
+        },
+        ("AuthenticAMD", 0x1a) => Some("Zen 5"),
+        ("HygonGenuine", 0x18) => Some("Dhyana"),
+        ("GenuineIntel", 6) => match model {
+            0x2a | 0x2d => Some("Sandy Bridge"),
+            0x3a | 0x3e => Some("Ivy Bridge"),
+            0x3c | 0x3f | 0x45 | 0x46 => Some("Haswell"),
+            0x3d | 0x47 | 0x4f | 0x56 => Some("Broadwell"),
+            0x4e | 0x5e | 0x55 => Some("Skylake"),
+            0x8e | 0x9e => Some("Kaby Lake / Coffee Lake"),
+            0xa5 | 0xa6 => Some("Comet Lake"),
+            0x7d | 0x7e | 0x6a | 0x6c => Some("Ice Lake"),
+            0x8c | 0x8d => Some("Tiger Lake"),
+            0xa7 => Some("Rocket Lake"),
+            0x97 | 0x9a | 0xbe => Some("Alder Lake"),
+            0xb7 | 0xba | 0xbf => Some("Raptor Lake"),
+            0x8f => Some("Sapphire Rapids"),
+            0xcf => Some("Emerald Rapids"),
+            0xaa | 0xac => Some("Meteor Lake"),
+            0xbd => Some("Lunar Lake"),
+            0xc5 | 0xc6 => Some("Arrow Lake"),
+            0x5c | 0x5f | 0x7a => Some("Goldmont"),
+            0x86 | 0x96 | 0x9c => Some("Tremont"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The `temp_sensor` reading, or the best-ranked CPU sensor when it is
+/// unset or missing.
+fn get_cpu_temp(config: &Config) -> Option<f64> {
+    let sensors = hwmon::temperatures();
+    if let Some(wanted) = &config.temp_sensor {
+        if let Some((_, celsius)) = sensors.iter().find(|(name, _)| name == wanted) {
+            return Some(*celsius);
+        }
+        if config.verbose {
+            eprintln!("rfetch: temp_sensor `{}` not found, picking one (`rfetch sensors` lists them)", wanted);
+        }
+    }
+    sensors
+        .iter()
+        .filter_map(|(name, celsius)| Some((hwmon::cpu_sensor_rank(name)?, *celsius)))
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, celsius)| celsius)
+}
+
+/// Average CPU package power in watts over a short sample, from RAPL's
+/// package energy counters under /sys/class/powercap (Intel, and AMD since
+/// Linux 5.8) or the amd_energy hwmon driver's per-socket counters. Recent
+/// kernels make the counters root-only; unreadable ones mean no value.
+fn get_cpu_power() -> Option<f64> {
+    let start = std::time::Instant::now();
+    let before = cpu_energy_counters()?;
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let after = cpu_energy_counters()?;
+    let seconds = start.elapsed().as_secs_f64();
+    if before.len() != after.len() || seconds <= 0.0 {
+        return None;
+    }
+    let microjoules: u64 = before
+        .iter()
+        .zip(&after)
+        .map(|(&(before, range), &(after, _))| energy_delta(before, after, range))
+        .sum();
+    Some(microjoules as f64 / 1e6 / seconds)
+}
+
+/// Each CPU package's energy counter and the value it wraps at, in µJ.
+fn cpu_energy_counters() -> Option<Vec<(u64, u64)>> {
+    let mut zones: Vec<_> = fs::read_dir("/sys/class/powercap")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        // `intel-rapl:0` is a package; `intel-rapl:0:0` is a core or uncore
+        // domain inside it, already counted.
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.matches(':').count() == 1))
+        .filter(|path| fs::read_to_string(path.join("name")).is_ok_and(|name| name.starts_with("package")))
+        .collect();
+    zones.sort();
+    let rapl: Option<Vec<_>> = zones
+        .iter()
+        .map(|zone| Some((read_u64(&zone.join("energy_uj"))?, read_u64(&zone.join("max_energy_range_uj"))?)))
+        .collect();
+    if let Some(rapl) = rapl.filter(|counters| !counters.is_empty()) {
+        return Some(rapl);
+    }
+
+    // amd_energy's counters are accumulated to 64 bits by the driver.
+    let sockets: Vec<_> = hwmon::chips()
+        .into_iter()
+        .filter(|chip| chip.name == "amd_energy")
+        .flat_map(|chip| chip.sensors("energy"))
+        .filter(|sensor| sensor.label.starts_with("Esocket"))
+        .map(|sensor| (sensor.input as u64, u64::MAX))
+        .collect();
+    (!sockets.is_empty()).then_some(sockets)
+}
+
+/// How far an energy counter moved, allowing for one wrap at `range`.
+fn energy_delta(before: u64, after: u64, range: u64) -> u64 {
+    if after >= before { after - before } else { range.saturating_sub(before).saturating_add(after) }
+}
+
+/// Whether the CPU is thermal-throttling right now: some thermal zone is at
+/// or past a passive (throttle) trip point. Without trip points, Intel's
+/// per-core throttle counters are sampled twice and rising counts mean yes.
+fn get_throttling() -> Option<bool> {
+    let read_millidegrees = |path: &Path| fs::read_to_string(path).ok()?.trim().parse::<i64>().ok();
+    let mut zones_with_trips = 0;
+    let mut throttling = false;
+    for entry in fs::read_dir("/sys/class/thermal").ok()?.flatten() {
+        let zone = entry.path();
+        if !entry.file_name().to_string_lossy().starts_with("thermal_zone") {
+            continue;
+        }
+        let Some(temp) = read_millidegrees(&zone.join("temp")) else {
+            continue;
+        };
+        let mut passive_trips = Vec::new();
+        for n in 0.. {
+            let Ok(kind) = fs::read_to_string(zone.join(format!("trip_point_{}_type", n))) else {
+                break;
+            };
+            if kind.trim() == "passive"
+                && let Some(trip) = read_millidegrees(&zone.join(format!("trip_point_{}_temp", n)))
+                && trip > 0
+            {
+                passive_trips.push(trip);
+            }
+        }
+        if !passive_trips.is_empty() {
+            zones_with_trips += 1;
+            throttling |= passive_trips.iter().any(|&trip| temp >= trip);
+        }
+    }
+    if zones_with_trips > 0 {
+        return Some(throttling);
+    }
+
+    let throttle_events = || -> Option<u64> {
+        let counts: Vec<u64> = fs::read_dir("/sys/devices/system/cpu")
+            .ok()?
+            .flatten()
+            .filter_map(|e| read_u64(&e.path().join("thermal_throttle/core_throttle_count")))
+            .collect();
+        (!counts.is_empty()).then(|| counts.iter().sum())
+    };
+    let before = throttle_events()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    Some(throttle_events()? > before)
+}
+
+/// The desktop's UI scale, e.g. `2x` or `1.25x (text)`. Toolkit variables
+/// override the desktop's own setting, so they are checked first.
+fn get_scale() -> Option<String> {
+    let format = |factor: f64| format!("{}x", (factor * 100.0).round() / 100.0);
+    let env_factor = |name: &str| std::env::var(name).ok()?.trim().parse::<f64>().ok().filter(|f| *f > 0.0);
+
+    if let Some(factor) = env_factor("GDK_SCALE").or_else(|| env_factor("QT_SCALE_FACTOR")) {
+        return Some(format(factor));
+    }
+
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    if desktop.contains("GNOME") && command_exists("gsettings") {
+        let get = |key: &str| -> Option<String> {
+            let output = Command::new("gsettings")
+                .args(["get", "org.gnome.desktop.interface", key])
+                .output()
+                .ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+        // `uint32 2`; 0 means "pick automatically", which tells us nothing.
+        let scaling = get("scaling-factor")
+            .and_then(|v| v.trim_start_matches("uint32 ").parse::<f64>().ok())
+            .filter(|f| *f > 0.0);
+        if let Some(factor) = scaling {
+            return Some(format(factor));
+        }
+        let text = get("text-scaling-factor").and_then(|v| v.parse::<f64>().ok());
+        if let Some(factor) = text.filter(|f| *f != 1.0) {
+            return Some(format!("{} (text)", format(factor)));
+        }
+    }
+    if desktop.contains("KDE") {
+        // `ScaleFactor=1.5` under `[KScreen]` in kdeglobals.
+        let path = config::config_home()?.join("kdeglobals");
+        let content = fs::read_to_string(path).ok()?;
+        let factor = content
+            .lines()
+            .skip_while(|line| line.trim() != "[KScreen]")
+            .skip(1)
+            .take_while(|line| !line.starts_with('['))
+            .find_map(|line| line.trim().strip_prefix("ScaleFactor="))?
+            .parse::<f64>()
+            .ok()?;
+        return Some(format(factor));
+    }
+    None
+}
+
+/// Connected monitors, from the DRM connectors in sysfs. The size is the
+/// monitor's preferred mode, which the kernel lists first in `modes`.
+fn get_displays() -> Vec<Display> {
+    let Ok(entries) = fs::read_dir("/sys/class/drm") else {
+        return Vec::new();
+    };
+    let mut displays: Vec<Display> = entries
+        .flatten()
+        .filter_map(|entry| {
+            // Connectors are named `card0-HDMI-A-1`; plain `card0` is the GPU.
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let (_, connector) = name.split_once('-')?;
+            let path = entry.path();
+            if fs::read_to_string(path.join("status")).ok()?.trim() != "connected" {
+                return None;
+            }
+            let modes = fs::read_to_string(path.join("modes")).ok()?;
+            let (width, height) = modes.lines().next()?.split_once('x')?;
+            Some(Display {
+                connector: connector.to_string(),
+                width: width.parse().ok()?,
+                // Interlaced modes carry an `i` suffix.
+                height: height.trim_end_matches('i').parse().ok()?,
+                size_mm: fs::read(path.join("edid")).ok().and_then(|edid| edid_size_mm(&edid)),
+            })
+        })
+        .collect();
+    displays.sort_by(|a, b| a.connector.cmp(&b.connector));
+    displays
+}
+
+fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// RAM modules from `dmidecode --type memory`, which needs root to read
+/// the SMBIOS tables.
+fn get_memory_modules() -> Vec<MemoryModules> {
+    let Ok(output) = Command::new("dmidecode").args(["--type", "memory"]).output() else {
+        return Vec::new();
+    };
+    parse_memory_devices(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Groups the populated `Memory Device` entries of dmidecode's output.
+fn parse_memory_devices(text: &str) -> Vec<MemoryModules> {
+    let mut groups: Vec<MemoryModules> = Vec::new();
+    for device in text.split("\n\n").filter(|block| block.lines().any(|l| l.trim() == "Memory Device")) {
+        let value = |key: &str| {
+            device
+                .lines()
+                .find_map(|line| line.trim().strip_prefix(key)?.strip_prefix(':'))
+                .map(str::trim)
+                .filter(|v| !v.is_empty() && *v != "Unknown")
+        };
+        // `16 GB`, or `16384 MB` from older dmidecode. Empty slots say
+        // `No Module Installed`.
+        let Some(size) = value("Size").and_then(|size| {
+            let (number, unit) = size.split_once(' ')?;
+            let number: u64 = number.parse().ok()?;
+            let exp = match unit {
+                "kB" | "KB" => 1,
+                "MB" => 2,
+                "GB" => 3,
+                "TB" => 4,
+                _ => return None,
+            };
+            number.checked_mul(1024u64.pow(exp))
+        }) else {
+            continue;
+        };
+        let kind = value("Type").map(str::to_string);
+        // `3200 MT/s`, or `3200 MHz` before dmidecode 3.3.
+        let speed = value("Speed").and_then(|s| s.split_whitespace().next()?.parse().ok());
+        match groups.iter_mut().find(|g| g.size == size && g.kind == kind && g.speed == speed) {
+            Some(group) => group.count += 1,
+            None => groups.push(MemoryModules { count: 1, size, kind, speed }),
+        }
+    }
+    groups
+}
+
+/// The physical image size an EDID blob reports, in millimetres. Prefers the
+/// first detailed timing descriptor, which has millimetre precision, over
+/// the base block's whole centimetres. `None` for a bad header or checksum,
+/// and for projectors and TVs that leave the size out.
+fn edid_size_mm(edid: &[u8]) -> Option<(u32, u32)> {
+    const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+    let base = edid.get(..128)?;
+    if base[..8] != HEADER || base.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+        return None;
+    }
+    // Bytes 54..72: a pixel clock of 0 marks a display descriptor instead.
+    let dtd = &base[54..72];
+    if dtd[0] != 0 || dtd[1] != 0 {
+        let width = dtd[12] as u32 | ((dtd[14] as u32 & 0xf0) << 4);
+        let height = dtd[13] as u32 | ((dtd[14] as u32 & 0x0f) << 8);
+        if width > 0 && height > 0 {
+            return Some((width, height));
+        }
+    }
+    let (width, height) = (base[21] as u32, base[22] as u32);
+    // One of them being 0 means the pair is an aspect ratio, not a size.
+    (width > 0 && height > 0).then_some((width * 10, height * 10))
+}
+
+/// `4.6 (AMD Radeon RX 6700 XT)` from `glxinfo -B`.
+fn get_opengl() -> Option<String> {
+    let output = Command::new("glxinfo").arg("-B").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let value = |key: &str| text.lines().find_map(|line| line.trim().strip_prefix(key)).map(str::trim);
+    // `4.6 (Compatibility Profile) Mesa 23.1.0`
+    let version = value("OpenGL version string:")?.split_whitespace().next()?;
+    // `AMD Radeon RX 6700 XT (navi22, LLVM 15.0.7, DRM 3.49, 6.1.0)`
+    let renderer = value("OpenGL renderer string:").map(|r| r.split(" (").next().unwrap_or(r));
+    Some(match renderer {
+        Some(renderer) => format!("{} ({})", version, renderer),
+        None => version.to_string(),
+    })
+}
+
+/// `1.3.246 (AMD Radeon RX 6700 XT)` for the first device in
+/// `vulkaninfo --summary`.
+fn get_vulkan() -> Option<String> {
+    let output = Command::new("vulkaninfo").arg("--summary").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    // The device list comes after the instance section, which has its own
+    // version line; `apiVersion = 1.3.246` and `deviceName = ...` are per GPU.
+    let devices = text.split_once("Devices:").map_or(text.as_ref(), |(_, rest)| rest);
+    let value = |key: &str| {
+        devices.lines().find_map(|line| {
+            let (name, value) = line.split_once('=')?;
+            (name.trim() == key).then(|| value.trim())
+        })
+    };
+    // Older drivers append the raw number: `1.3.246 (4206838)`.
+    let version = value("apiVersion")?.split_whitespace().next()?;
+    // Mesa tags the name with its driver: `... (RADV NAVI22)`.
+    let name = value("deviceName").map(|n| n.split(" (").next().unwrap_or(n));
+    Some(match name {
+        Some(name) => format!("{} ({})", version, name),
+        None => version.to_string(),
+    })
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn gpu_vendor(vendor: u16) -> Option<&'static str> {
+    match vendor {
+        0x1002 => Some("AMD"),
+        0x10de => Some("NVIDIA"),
+        0x8086 => Some("Intel"),
+        0x1af4 => Some("Virtio"),
+        0x15ad => Some("VMware"),
+        0x1234 => Some("QEMU"),
+        _ => None,
+    }
+}
+
+/// Looks the device up in a `pci.ids` database, preferring the marketing
+/// name in brackets (`Navi 22 [Radeon RX 6700 XT]` becomes `Radeon RX 6700 XT`).
+fn gpu_name(ids: &str, vendor: u16, device: u16) -> String {
+    let vendor_hex = format!("{:04x}", vendor);
+    let device_hex = format!("{:04x}", device);
+
+    let mut vendor_name = gpu_vendor(vendor).map(str::to_string);
+    let mut device_name = None;
+    let mut in_vendor = false;
+    for line in ids.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if !line.starts_with('\t') {
+            if in_vendor {
+                break;
+            }
+            if let Some(name) = line.strip_prefix(&vendor_hex) {
+                in_vendor = true;
+                vendor_name.get_or_insert_with(|| name.trim().to_string());
+            }
+        } else if in_vendor
+            && !line.starts_with("\t\t")
+            && let Some(name) = line[1..].strip_prefix(&device_hex)
+        {
+            device_name = Some(name.trim().to_string());
+            break;
+        }
+    }
+
+    let vendor_name = vendor_name.unwrap_or_else(|| format!("Vendor {}", vendor_hex));
+    match device_name {
+        Some(name) => {
+            let marketing = name
+                .split_once('[')
+                .and_then(|(_, rest)| rest.split_once(']'))
+                .map(|(inner, _)| inner.to_string());
+            format!("{} {}", vendor_name, marketing.unwrap_or(name))
+        }
+        None => format!("{} Device {}", vendor_name, device_hex),
+    }
+}
+
+/// Integrated vs discrete, from the PCI vendor and what the driver reports:
+/// - Intel is integrated, except Arc cards (device ids 0x56xx and 0xe2xx);
+/// - NVIDIA is discrete;
+/// - AMD is discrete when amdgpu reports at least 2 GiB of dedicated VRAM,
+///   since APUs only get a small carve-out of system memory;
+/// - virtual adapters (virtio, VMware, QEMU) count as integrated.
+fn classify_gpu(vendor: u16, device: u16, vram: Option<u64>) -> GpuKind {
+    match vendor {
+        0x8086 if matches!(device >> 8, 0x56 | 0xe2) => GpuKind::Discrete,
+        0x8086 | 0x1af4 | 0x15ad | 0x1234 => GpuKind::Integrated,
+        0x10de => GpuKind::Discrete,
+        0x1002 => match vram {
+            Some(bytes) if bytes >= 2 << 30 => GpuKind::Discrete,
+            Some(_) => GpuKind::Integrated,
+            None => GpuKind::Unknown,
+        },
+        _ => GpuKind::Unknown,
+    }
+}
+
+/// Laptops default to showing their discrete GPU, desktops list everything.
+fn default_gpu_mode() -> GpuMode {
+    if is_laptop() { GpuMode::Discrete } else { GpuMode::All }
+}
+
+fn is_laptop() -> bool {
+    // SMBIOS chassis types: portable, laptop, notebook, sub-notebook,
+    // convertible and detachable.
+    let chassis = fs::read_to_string("/sys/class/dmi/id/chassis_type").unwrap_or_default();
+    if matches!(chassis.trim().parse(), Ok(8 | 9 | 10 | 14 | 31 | 32)) {
+        return true;
+    }
+    fs::read_dir("/sys/class/power_supply")
+        .map(|entries| entries.flatten().any(|e| e.file_name().to_string_lossy().starts_with("BAT")))
+        .unwrap_or(false)
+}
+
+fn select_gpus(gpus: Vec<Gpu>, mode: GpuMode) -> Vec<Gpu> {
+    match mode {
+        GpuMode::All => gpus,
+        GpuMode::Discrete => {
+            if gpus.iter().any(|g| g.kind == GpuKind::Discrete) {
+                gpus.into_iter().filter(|g| g.kind == GpuKind::Discrete).collect()
+            } else {
+                gpus
+            }
+        }
+        GpuMode::Primary => {
+            let primary = primary_gpu(&gpus);
+            gpus.into_iter().skip(primary).take(1).collect()
+        }
+    }
+}
+
+/// Index of the GPU doing the rendering: the one `DRI_PRIME` asks for, or
+/// else the one the firmware booted the display on.
+fn primary_gpu(gpus: &[Gpu]) -> usize {
+    let boot = gpus.iter().position(|g| g.boot_vga).unwrap_or(0);
+    match std::env::var("DRI_PRIME").ok().as_deref() {
+        None | Some("") | Some("0") => boot,
+        // `DRI_PRIME=pci-0000_01_00_0` names a device exactly.
+        Some(tag) if tag.starts_with("pci-") => {
+            let pci = tag[4..].replacen('_', ":", 2).replacen('_', ".", 1);
+            gpus.iter().position(|g| g.pci == pci).unwrap_or(boot)
+        }
+        // `DRI_PRIME=1` means "the other one".
+        Some(_) => gpus.iter().position(|g| !g.boot_vga).unwrap_or(boot),
+    }
+}
+
+fn get_memory() -> Option<Usage> {
+    let content = fs::read_to_string("/proc/meminfo").ok()?;
+
+    let mut total = 0;
+    let mut available = 0;
+
+    for line in content.lines() {
+        if line.starts_with("MemTotal:") {
+            total = extract_kb(line);
+        } else if line.starts_with("MemAvailable: ") {
+            available = extract_kb(line);
+        }
+    }
+
+    if total == 0 {
+        return None;
+    }
+
+    let used = total.saturating_sub(available);
+    Some(Usage { used: used.saturating_mul(1024), total: total.saturating_mul(1024) })
+}
+
+fn extract_kb(line: &str) -> u64 {
+    line.split_whitespace()
+        .nth(1)
+        .unwrap_or("0")
+        .parse()
+        .unwrap_or(0)
+}
+
+fn get_swap() -> Option<Usage> {
+    let mut sys = System::new_all();
+    sys.refresh_memory();
+
+    Some(Usage { used: sys.used_swap(), total: sys.total_swap() })
+}
+
+fn get_swap_devices() -> Vec<SwapDevice> {
+    let Ok(content) = fs::read_to_string("/proc/swaps") else {
+        return Vec::new();
+    };
+    // Filename Type Size Used Priority, sizes in KiB, after a header line.
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = unescape_mount(parts.next()?);
+            let kind = parts.next()?;
+            let total: u64 = parts.next()?.parse().ok()?;
+            let used: u64 = parts.next()?.parse().ok()?;
+            let kind = if name.starts_with("/dev/zram") { "zram" } else { kind };
+            Some(SwapDevice {
+                name,
+                kind: kind.to_string(),
+                usage: Usage { used: used.saturating_mul(1024), total: total.saturating_mul(1024) },
+            })
+        })
+        .collect()
+}
+
+/// Filesystems whose "disk" is on another machine. Their space is the
+/// server's and stat-ing them can hang, so we only show where they point.
+const NETWORK_FILESYSTEMS: &[&str] = &["nfs", "nfs4", "cifs", "smb3", "fuse.sshfs"];
+
+fn get_storages(config: &Config) -> Vec<Storage> {
+    let disks = Disks::new_with_refreshed_list();
+    let mounts = read_mounts();
+    let is_network = |m: &MountEntry| NETWORK_FILESYSTEMS.contains(&m.fstype.as_str());
+
+    let paths: Vec<String> = match &config.storage {
+        StorageMounts::Paths(paths) => paths.clone(),
+        StorageMounts::Auto => {
+            let mut paths: Vec<String> = disks
+                .iter()
+                .map(|disk| disk.mount_point().to_string_lossy().into_owned())
+                .filter(|mount| !mounts.iter().any(|m| m.target == *mount && is_network(m)))
+                .collect();
+            if config.storage_network {
+                paths.extend(mounts.iter().filter(|m| is_network(m)).map(|m| m.target.clone()));
+            }
+            // Bind mounts and btrfs subvolumes show up once per mount point.
+            let mut seen = std::collections::HashSet::new();
+            paths.retain(|p| seen.insert(p.clone()));
+            paths
+        }
+    };
+
+    let mut storage: Vec<Storage> = paths
+        .iter()
+        .map(|path| {
+            guarded(Storage::unavailable(path), || {
+                // The mount holding `path`: the longest target that is a
+                // whole-component prefix of it.
+                let mount = mounts
+                    .iter()
+                    .filter(|m| {
+                        m.target == "/"
+                            || path == &m.target
+                            || path.strip_prefix(m.target.as_str()).is_some_and(|rest| rest.starts_with('/'))
+                    })
+                    .max_by_key(|m| m.target.len());
+                match mount {
+                    Some(m) if is_network(m) => Storage {
+                        mount: path.clone(),
+                        usage: None,
+                        filesystem: Some(m.fstype.clone()),
+                        notes: Vec::new(),
+                        source: Some(m.source.clone()),
+                        inodes: None,
+                    },
+                    _ => get_storage(&disks, &mounts, path, config),
+                }
+            })
+        })
+        .collect();
+    // Mounts the user listed by hand are wanted whatever their size.
+    if config.storage == StorageMounts::Auto {
+        storage.retain(|s| s.usage.is_none_or(|u| u.total >= config.storage_min_size));
+    }
+    match config.storage_sort {
+        StorageSort::None => {}
+        StorageSort::Size => storage.sort_by_key(|s| std::cmp::Reverse(s.usage.map(|u| u.total))),
+        StorageSort::Path => storage.sort_by(|a, b| a.mount.cmp(&b.mount)),
+    }
+    storage
+}
+
+fn get_storage(disks: &Disks, mounts: &[MountEntry], path: &str, config: &Config) -> Storage {
     let mut best_match = None;
-    for disk in &disks {
+    for disk in disks {
         let mount = disk.mount_point().to_string_lossy();
         if path.starts_with(mount.as_ref()) {
             match &best_match {
@@ -191,13 +2177,526 @@ fn get_storage(path: &str) -> String {
             }
         }
                     
-        if let Some((_, disk)) = best_match {
-            let total = disk.total_space() as f64 / 1_000_000_000.0;
-            let avail = disk.available_space() as f64 / 1_000_000_000.0;
-            let used = total - avail;
+        let Some((_, disk)) = best_match else {
+            return Storage::unavailable(path);
+        };
 
-            format!("{:.1} G / {:.1} G ({})", used, total, path)
+        let total = disk.total_space();
+        let avail = disk.available_space();
+        let mut usage = Usage { used: total.saturating_sub(avail), total };
+        let filesystem = disk.file_system().to_string_lossy().into_owned();
+        let mut notes = Vec::new();
+
+        // statfs numbers lie on pooled / compressed filesystems, so ask the
+        // filesystem's own tools when they are installed.
+        let mount_point = disk.mount_point().to_string_lossy();
+        let entry = mounts.iter().rev().find(|m| m.target == mount_point);
+        match filesystem.as_str() {
+            "btrfs" => {
+                if let Some(real) = btrfs_usage(&mount_point) {
+                    usage = real;
+                }
+                notes.push("btrfs".to_string());
+                if entry.is_some_and(|m| m.options.iter().any(|o| o.starts_with("compress"))) {
+                    notes.push("compressed".to_string());
+                }
+            }
+            "zfs" => {
+                if let Some(real) = entry.and_then(|m| zfs_usage(&m.source)) {
+                    usage = real;
+                }
+                notes.push("zfs".to_string());
+            }
+            // Immutable distros put an overlay on `/`, whose merged size
+            // is the read-only lower layer's, not what can still be written.
+            "overlay" => {
+                notes.push("overlay".to_string());
+                if config.storage_overlay_upper
+                    && let Some(upper) = entry.and_then(|m| mount_option(&m.options, "upperdir"))
+                    && let Some(real) = space_usage(upper)
+                {
+                    usage = real;
+                    notes.push("upper layer".to_string());
+                }
+            }
+            _ => {}
+        }
+        if entry.is_some_and(|m| m.options.iter().any(|o| o == "ro")) {
+            notes.push("ro".to_string());
+        }
+
+        let inodes = if config.storage_inodes { inode_usage(&mount_point) } else { None };
+        Storage { mount: path.to_string(), usage: Some(usage), filesystem: Some(filesystem), notes, source: None, inodes }
+    }
+
+fn statvfs(path: &str) -> Option<libc::statvfs> {
+    let path = std::ffi::CString::new(path).ok()?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    (unsafe { libc::statvfs(path.as_ptr(), &mut stats) } == 0).then_some(stats)
+}
+
+/// Inodes used and in total on the filesystem at `mount`, from statvfs.
+/// btrfs and others without a fixed inode table report a total of 0,
+/// which gives `None`.
+fn inode_usage(mount: &str) -> Option<Usage> {
+    let stats = statvfs(mount)?;
+    let total = stats.f_files;
+    (total > 0).then(|| Usage { used: total.saturating_sub(stats.f_ffree), total })
+}
+
+/// Space used and in total on the filesystem holding `path`, counted the
+/// way sysinfo counts a disk: what is not available to us is used.
+fn space_usage(path: &str) -> Option<Usage> {
+    let stats = statvfs(path)?;
+    let block = stats.f_frsize;
+    let total = stats.f_blocks * block;
+    Some(Usage { used: total.saturating_sub(stats.f_bavail * block), total })
+}
+
+/// The value of a `key=value` mount option.
+fn mount_option<'a>(options: &'a [String], key: &str) -> Option<&'a str> {
+    options.iter().find_map(|o| o.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// One line of `/proc/mounts`.
+struct MountEntry {
+    source: String,
+    target: String,
+    fstype: String,
+    options: Vec<String>,
+}
+
+fn read_mounts() -> Vec<MountEntry> {
+    fs::read_to_string("/proc/mounts").map(|content| parse_mounts(&content)).unwrap_or_default()
+}
+
+fn parse_mounts(content: &str) -> Vec<MountEntry> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let source = unescape_mount(parts.next()?);
+            let target = unescape_mount(parts.next()?);
+            let fstype = parts.next()?.to_string();
+            let options = parts.next()?.split(',').map(str::to_string).collect();
+            Some(MountEntry { source, target, fstype, options })
+        })
+        .collect()
+}
+
+/// `/proc/mounts` encodes spaces, tabs and backslashes as `\040`-style octal.
+fn unescape_mount(field: &str) -> String {
+    let mut out = String::new();
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let octal: String = chars.by_ref().take(3).collect();
+            match u8::from_str_radix(&octal, 8) {
+                Ok(byte) => out.push(byte as char),
+                Err(_) => {
+                    out.push(c);
+                    out.push_str(&octal);
+                }
+            }
         } else {
-            format!("N/A ({})", path)
+            out.push(c);
         }
     }
+    out
+}
+
+fn btrfs_usage(mount: &str) -> Option<Usage> {
+    let output = Command::new("btrfs")
+        .args(["filesystem", "usage", "-b", mount])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let field = |name: &str| -> Option<u64> {
+        text.lines()
+            .find_map(|line| line.trim().strip_prefix(name))?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    };
+    Some(Usage { used: field("Used:")?, total: field("Device size:")? })
+}
+
+fn zfs_usage(dataset: &str) -> Option<Usage> {
+    let output = Command::new("zfs")
+        .args(["list", "-Hp", "-o", "used,available", dataset])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut values = text.split_whitespace().map(|v| v.parse::<u64>().ok());
+    let used = values.next()??;
+    let available = values.next()??;
+    Some(Usage { used, total: used.saturating_add(available) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A throwaway directory standing in for `/`, holding just what one of
+    /// the `*_in` collectors reads.
+    struct Fixture {
+        root: PathBuf,
+    }
+
+    impl Fixture {
+        fn new(name: &str) -> Fixture {
+            let root = std::env::temp_dir().join(format!("rfetch-test-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            Fixture { root }
+        }
+
+        fn file(self, path: &str, contents: impl AsRef<[u8]>) -> Fixture {
+            let path = self.root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+            self
+        }
+
+        fn dir(self, path: &str) -> Fixture {
+            fs::create_dir_all(self.root.join(path)).unwrap();
+            self
+        }
+
+        fn symlink(self, path: &str, target: impl AsRef<Path>) -> Fixture {
+            let path = self.root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::os::unix::fs::symlink(target, path).unwrap();
+            self
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    /// A root whose PID 1 is `comm`, for `detect_init_in`.
+    fn init_root(name: &str, comm: &str) -> Fixture {
+        Fixture::new(name).file("proc/1/comm", format!("{}\n", comm))
+    }
+
+    #[test]
+    fn init_from_comm() {
+        let cases = [
+            ("systemd", "systemd"),
+            ("runit", "runit"),
+            ("runsvinit", "runit"),
+            ("runit-init", "runit"),
+            ("dinit", "dinit"),
+            ("openrc-init", "openrc"),
+            ("launchd", "launchd"),
+            ("s6-svscan", "s6"),
+            ("init", "sysvinit"),
+        ];
+        for (comm, expected) in cases {
+            assert_eq!(detect_init_in(&init_root(comm, comm).root), expected, "comm `{}`", comm);
+        }
+    }
+
+    #[test]
+    fn init_openrc_from_exe() {
+        let fixture = init_root("openrc", "init").symlink("proc/1/exe", "/sbin/openrc-init");
+        assert_eq!(detect_init_in(&fixture.root), "openrc");
+        let fixture = init_root("sysvinit-exe", "init").symlink("proc/1/exe", "/sbin/init");
+        assert_eq!(detect_init_in(&fixture.root), "sysvinit");
+    }
+
+    #[test]
+    fn init_s6_variants() {
+        let fixture = init_root("s6-rc", "s6-svscan").dir("run/s6-rc").dir("etc/s6-linux-init");
+        assert_eq!(detect_init_in(&fixture.root), "s6-rc");
+        let fixture = init_root("s6-linux-init", "s6-svscan").dir("etc/s6-linux-init");
+        assert_eq!(detect_init_in(&fixture.root), "s6-linux-init");
+    }
+
+    #[test]
+    fn init_under_wsl() {
+        let fixture = init_root("wsl", "init")
+            .symlink("proc/1/exe", "/init")
+            .file("proc/sys/kernel/osrelease", "5.15.90.1-microsoft-standard-WSL2\n");
+        assert_eq!(detect_init_in(&fixture.root), "none (WSL)");
+        assert_eq!(wsl_version_in(&fixture.root), Some(2));
+        fs::write(fixture.root.join("proc/sys/kernel/osrelease"), "4.4.0-19041-Microsoft\n").unwrap();
+        assert_eq!(wsl_version_in(&fixture.root), Some(1));
+    }
+
+    #[test]
+    fn chroot_detection() {
+        let fixture = Fixture::new("chroot");
+        let root = fixture.root.clone();
+        let fixture = fixture.symlink("proc/1/root", root);
+        assert_eq!(chroot_in(&fixture.root), Some(false));
+        let fixture = Fixture::new("chrooted").symlink("proc/1/root", "/");
+        assert_eq!(chroot_in(&fixture.root), Some(true));
+        assert_eq!(chroot_in(&Fixture::new("chroot-hidden").root), None);
+    }
+
+    #[test]
+    fn init_systemd_fallback() {
+        // In containers PID 1 is often a shell or a wrapper.
+        let fixture = init_root("fallback", "bash").dir("run/systemd/systemd");
+        assert_eq!(detect_init_in(&fixture.root), "systemd (fallback)");
+    }
+
+    #[test]
+    fn init_unknown() {
+        assert_eq!(detect_init_in(&init_root("unknown", "tini").root), "unknown (tini)");
+        assert_eq!(detect_init_in(&Fixture::new("unreadable").root), "unknown ()");
+    }
+
+    #[test]
+    fn os_release_falls_back_to_usr_lib() {
+        let usr = "NAME=Fedora\nPRETTY_NAME=\"Fedora Linux 40 (Silverblue)\"\n";
+        let fixture = Fixture::new("os-usr-lib").file("usr/lib/os-release", usr);
+        assert_eq!(get_os_in(&fixture.root), "Fedora Linux 40 (Silverblue)");
+        let fixture = fixture.file("etc/os-release", "PRETTY_NAME=\"Custom\"\n");
+        assert_eq!(get_os_in(&fixture.root), "Custom");
+        assert_eq!(get_os_in(&Fixture::new("os-none").root), "unknown");
+    }
+
+    #[test]
+    fn microarch_lookup() {
+        assert_eq!(microarch_name("AuthenticAMD", 25, 0x21), Some("Zen 3"));
+        assert_eq!(microarch_name("AuthenticAMD", 25, 0x61), Some("Zen 4"));
+        assert_eq!(microarch_name("AuthenticAMD", 23, 0x71), Some("Zen 2"));
+        assert_eq!(microarch_name("AuthenticAMD", 23, 0x08), Some("Zen+"));
+        assert_eq!(microarch_name("GenuineIntel", 6, 0x97), Some("Alder Lake"));
+        assert_eq!(microarch_name("GenuineIntel", 6, 0x01), None);
+        assert_eq!(microarch_name("GenuineIntel", 15, 0x04), None);
+    }
+
+    #[test]
+    fn edid_sizes() {
+        let mut edid = vec![0u8; 128];
+        edid[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+        // 60 x 34 cm in the base block.
+        edid[21] = 60;
+        edid[22] = 34;
+        let checksum = |edid: &mut Vec<u8>| {
+            edid[127] = 0;
+            edid[127] = edid.iter().fold(0u8, |sum, &b| sum.wrapping_sub(b));
+        };
+        checksum(&mut edid);
+        assert_eq!(edid_size_mm(&edid), Some((600, 340)));
+
+        // 597 x 336 mm in the first detailed timing descriptor.
+        edid[54] = 0x56;
+        edid[66] = (597 & 0xff) as u8;
+        edid[67] = (336 & 0xff) as u8;
+        edid[68] = ((597 >> 8) << 4 | (336 >> 8)) as u8;
+        checksum(&mut edid);
+        assert_eq!(edid_size_mm(&edid), Some((597, 336)));
+
+        edid[127] = edid[127].wrapping_add(1);
+        assert_eq!(edid_size_mm(&edid), None);
+        assert_eq!(edid_size_mm(&edid[..64]), None);
+    }
+
+    #[test]
+    fn psi_parsing() {
+        let memory = "some avg10=1.25 avg60=0.40 avg300=0.10 total=987654\n\
+                      full avg10=0.50 avg60=0.20 avg300=0.05 total=123456\n";
+        assert_eq!(psi_some_avg10(memory), Some(1.25));
+        // Older kernels have no `full` line for cpu.
+        assert_eq!(psi_some_avg10("some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n"), Some(0.0));
+        assert_eq!(psi_some_avg10("full avg10=0.50 avg60=0.20 avg300=0.05 total=1\n"), None);
+        assert_eq!(psi_some_avg10(""), None);
+    }
+
+    #[test]
+    fn uptime_parsing() {
+        assert_eq!(parse_uptime("0.00 0.00\n", 4), Some((0, None)));
+        assert_eq!(parse_uptime("3600.00 11520.00\n", 4), Some((3600, Some(80.0))));
+        // Idle can exceed uptime x CPUs when CPUs were hot-unplugged.
+        assert_eq!(parse_uptime("100.0 900.0", 4), Some((100, Some(100.0))));
+        assert_eq!(parse_uptime("1e30 0.0", 1), Some((u64::MAX, Some(0.0))));
+        assert_eq!(parse_uptime("3600.0 garbage", 4), Some((3600, None)));
+        assert_eq!(parse_uptime("3600.0", 0), Some((3600, None)));
+        assert_eq!(parse_uptime("-5.0 1.0", 4), None);
+        assert_eq!(parse_uptime("NaN 1.0", 4), None);
+        assert_eq!(parse_uptime("", 4), None);
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn default_route_parsing() {
+        let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+                      wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\n\
+                      eth0\t00000000\t010200C0\t0003\t0\t0\t100\t00000000\n\
+                      eth0\t000200C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\n";
+        assert_eq!(parse_default_route(routes), Some(("eth0".to_string(), Ipv4Addr::new(192, 0, 2, 1))));
+        assert_eq!(parse_default_route("Iface\tDestination\tGateway\n"), None);
+    }
+
+    #[test]
+    fn dns_servers() {
+        let resolv = "# Generated by NetworkManager\nsearch lan\nnameserver 192.168.1.1\nnameserver\t1.1.1.1 # cloudflare\n";
+        assert_eq!(parse_resolv_conf(resolv), ["192.168.1.1", "1.1.1.1"]);
+        let resolvectl = "Global: 1.1.1.1#cloudflare-dns.com\n\
+                          Link 2 (wlan0): 192.168.1.1 fe80::1%wlan0\n\
+                          Link 3 (eth0): 192.168.1.1\n\
+                          Link 4 (docker0):\n";
+        assert_eq!(parse_resolvectl_dns(resolvectl), ["1.1.1.1", "192.168.1.1", "fe80::1"]);
+
+        let servers = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(is_resolved_stub(&servers(&["127.0.0.53"])));
+        assert!(is_resolved_stub(&servers(&["127.0.0.53", "127.0.0.54"])));
+        assert!(!is_resolved_stub(&servers(&["127.0.0.5"])));
+        assert!(!is_resolved_stub(&servers(&["127.0.0.55"])));
+        assert!(!is_resolved_stub(&servers(&["127.0.0.53", "1.1.1.1"])));
+        assert!(!is_resolved_stub(&[]));
+    }
+
+    #[test]
+    fn memory_devices_grouped() {
+        let text = "Handle 0x0011, DMI type 17, 92 bytes\nMemory Device\n\tSize: 16 GB\n\tType: DDR4\n\tSpeed: 3200 MT/s\n\n\
+                    Handle 0x0012, DMI type 17, 92 bytes\nMemory Device\n\tSize: No Module Installed\n\tType: Unknown\n\n\
+                    Handle 0x0013, DMI type 17, 92 bytes\nMemory Device\n\tSize: 16384 MB\n\tType: DDR4\n\tSpeed: 3200 MHz\n\n\
+                    Handle 0x0014, DMI type 17, 92 bytes\nMemory Device\n\tSize: 8 GB\n\tType: DDR4\n\tSpeed: Unknown\n";
+        let gib = 1024 * 1024 * 1024;
+        let ddr4 = Some("DDR4".to_string());
+        assert_eq!(
+            parse_memory_devices(text),
+            [
+                MemoryModules { count: 2, size: 16 * gib, kind: ddr4.clone(), speed: Some(3200) },
+                MemoryModules { count: 1, size: 8 * gib, kind: ddr4, speed: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn active_scheduler() {
+        let (active, available) = parse_scheduler("mq-deadline kyber [bfq] none\n").unwrap();
+        assert_eq!(active, "bfq");
+        assert_eq!(available, ["mq-deadline", "kyber", "bfq", "none"]);
+        assert_eq!(parse_scheduler("none\n"), Some(("none".to_string(), vec!["none".to_string()])));
+        assert_eq!(parse_scheduler("mq-deadline none\n"), None);
+    }
+
+    #[test]
+    fn dpkg_counts_only_installed() {
+        let status = "Package: a\nStatus: install ok installed\n\n\
+                      Package: b\nStatus: hold ok installed\n\n\
+                      Package: c\nStatus: deinstall ok config-files\n";
+        assert_eq!(count_dpkg_installed(status), 2);
+    }
+
+    #[test]
+    fn apk_counts_packages() {
+        let installed = "C:Q1abc=\nP:musl\nV:1.2.5-r0\n\nC:Q1def=\nP:busybox\nV:1.36.1-r29\no:busybox\n";
+        assert_eq!(count_apk_installed(installed), 2);
+        assert_eq!(count_apk_installed(""), 0);
+    }
+
+    #[test]
+    fn lock_leds() {
+        let fixture = Fixture::new("locks")
+            .file("sys/class/leds/input3::capslock/brightness", "1\n")
+            .file("sys/class/leds/input3::numlock/brightness", "0\n")
+            .file("sys/class/leds/input9::numlock/brightness", "1\n")
+            .file("sys/class/leds/phy0-led/brightness", "1\n")
+            .file("sys/class/leds/platform::mute/brightness", "1\n");
+        assert_eq!(get_locks_in(&fixture.root), Some(Locks { caps: true, num: true, scroll: false }));
+        let fixture = Fixture::new("no-keyboard").file("sys/class/leds/tpacpi::power/brightness", "1\n");
+        assert_eq!(get_locks_in(&fixture.root), None);
+    }
+
+    #[test]
+    fn garbage_input() {
+        let non_utf8 = String::from_utf8_lossy(b"\xff\xfe \x80\n\xc3").into_owned();
+        let oversized = "9".repeat(1 << 20);
+        for text in ["", "\n\n", "some", "some avg10=", "#", &non_utf8, &oversized] {
+            assert_eq!(psi_some_avg10(text), None);
+            assert_eq!(parse_uptime(text, 4), None);
+            assert_eq!(parse_default_route(text), None);
+            assert_eq!(count_sockets(text), None);
+            assert_eq!(kernel::build_date(text), None);
+            assert_eq!(extract_kb(text), 0);
+            assert!(parse_resolv_conf(text).is_empty());
+            assert!(parse_resolvectl_dns(text).is_empty());
+            assert!(parse_memory_devices(text).is_empty());
+            assert!(parse_mounts(text).is_empty());
+        }
+
+        // Truncated or out-of-range records.
+        assert_eq!(extract_kb("MemTotal:"), 0);
+        assert_eq!(extract_kb("MemTotal: 99999999999999999999999 kB"), 0);
+        assert_eq!(parse_default_route("Iface\tDestination\nwlan0\t00000000\t0101"), None);
+        assert_eq!(parse_default_route("Iface\neth0\t00000000\tZZZZ\t0003\t0\t0\t100\n"), None);
+        assert_eq!(parse_scheduler("mq-deadline kyber"), None);
+        assert_eq!(parse_scheduler(""), None);
+        assert!(parse_mounts("/dev/sda1 /mnt ext4").is_empty());
+        assert_eq!(unescape_mount("a\\9x"), "a\\9x");
+        assert_eq!(unescape_mount("a\\"), "a\\");
+        assert_eq!(unescape_mount("\\777"), "\\777");
+        assert!(parse_memory_devices("Memory Device\n\tSize: 16\n").is_empty());
+        assert!(parse_memory_devices("Memory Device\n\tSize: 99999999999999999 TB\n").is_empty());
+        assert_eq!(edid_size_mm(&[0xff; 127]), None);
+
+        // Files that aren't UTF-8, or hold no number.
+        let fixture = Fixture::new("garbage")
+            .file("etc/os-release", b"PRETTY_NAME=\"\xff\xfe\"\n")
+            .file("proc/sys/kernel/osrelease", b"\xffmicrosoft\n")
+            .file("sys/class/power_supply/BAT0/type", b"\xff\xfe")
+            .file("sys/class/leds/input3::capslock/brightness", &oversized)
+            .file("number", b"\x80");
+        assert_eq!(get_os_in(&fixture.root), "unknown");
+        assert_eq!(wsl_version_in(&fixture.root), None);
+        assert_eq!(get_power_in(&fixture.root), Some(PowerSource::Ac));
+        assert_eq!(get_locks_in(&fixture.root), Some(Locks::default()));
+        assert_eq!(read_u64(&fixture.root.join("number")), None);
+        let fixture = Fixture::new("os-empty").file("etc/os-release", "NAME=Linux\nPRETTY_NAME=\"\"\n");
+        assert_eq!(get_os_in(&fixture.root), "unknown");
+    }
+
+    #[test]
+    fn power_source() {
+        let laptop = |online: &str| {
+            Fixture::new("power")
+                .file("sys/class/power_supply/ADP1/type", "Mains\n")
+                .file("sys/class/power_supply/ADP1/online", online)
+                .file("sys/class/power_supply/BAT0/type", "Battery\n")
+                .file("sys/class/power_supply/BAT0/scope", "System\n")
+        };
+        assert_eq!(get_power_in(&laptop("1\n").root), Some(PowerSource::Ac));
+        assert_eq!(get_power_in(&laptop("0\n").root), Some(PowerSource::Battery));
+        let desktop = Fixture::new("desktop-power")
+            .file("sys/class/power_supply/hidpp_battery_0/type", "Battery\n")
+            .file("sys/class/power_supply/hidpp_battery_0/scope", "Device\n");
+        assert_eq!(get_power_in(&desktop.root), Some(PowerSource::Ac));
+    }
+
+    #[test]
+    fn sockets_from_cpuinfo() {
+        let cpuinfo = "processor\t: 0\nphysical id\t: 0\n\nprocessor\t: 1\nphysical id\t: 1\n\n\
+                       processor\t: 2\nphysical id\t: 0\n";
+        assert_eq!(count_sockets(cpuinfo), Some(2));
+        assert_eq!(count_sockets("processor\t: 0\nBogoMIPS\t: 48.00\n"), None);
+    }
+
+    #[test]
+    fn overlay_mount_options() {
+        let mounts = parse_mounts(
+            "overlay / overlay rw,relatime,lowerdir=/usr,upperdir=/var/lib/upper,workdir=/var/lib/work 0 0\n\
+             /dev/vda3 /sysroot ext4 ro,relatime 0 0\n",
+        );
+        assert_eq!(mount_option(&mounts[0].options, "upperdir"), Some("/var/lib/upper"));
+        assert_eq!(mount_option(&mounts[0].options, "upper"), None);
+        assert!(mounts[1].options.iter().any(|o| o == "ro"));
+    }
+
+    #[test]
+    fn energy_counter_wraps() {
+        assert_eq!(energy_delta(1_000, 5_000, 262_143_328_850), 4_000);
+        assert_eq!(energy_delta(262_143_328_000, 150, 262_143_328_850), 1_000);
+    }
+}
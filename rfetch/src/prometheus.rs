@@ -0,0 +1,175 @@
+//! `--format prometheus`: the numeric parts of a fetch in the Prometheus
+//! text exposition format, for node_exporter's textfile collector.
+
+use std::fmt::{Display, Write};
+
+use crate::info::{SystemInfo, Usage};
+
+/// Labels of one sample, as name and value pairs.
+type Labels<'a> = Vec<(&'a str, &'a str)>;
+
+pub fn render(info: &SystemInfo) -> String {
+    let mut out = String::new();
+
+    if let Some(memory) = info.memory {
+        usage_families(&mut out, "memory", "Physical memory", &[(Vec::new(), memory)]);
+    }
+    if let Some(swap) = info.swap {
+        usage_families(&mut out, "swap", "Swap space", &[(Vec::new(), swap)]);
+    }
+    let storage: Vec<(Labels, Usage)> = info
+        .storage
+        .iter()
+        .filter_map(|storage| {
+            let fstype = storage.filesystem.as_deref().unwrap_or("");
+            Some((vec![("mount", storage.mount.as_str()), ("fstype", fstype)], storage.usage?))
+        })
+        .collect();
+    usage_families(&mut out, "storage", "Filesystem space", &storage);
+    let packages = info.packages.iter().map(|p| (vec![("source", p.source.as_str())], p.count));
+    family(&mut out, "packages", "Installed packages.", packages);
+    if let Some(uptime) = info.uptime {
+        gauge(&mut out, "uptime_seconds", "Seconds since boot.", uptime);
+    }
+    if let Some([load1, load5, load15]) = info.loadavg {
+        gauge(&mut out, "load1", "1 minute load average.", load1);
+        gauge(&mut out, "load5", "5 minute load average.", load5);
+        gauge(&mut out, "load15", "15 minute load average.", load15);
+    }
+
+    // String fields become `_info` metrics that are always 1, so they can
+    // be joined onto the numeric series by label.
+    let mut info_metric = |name: &str, label: &str, value: &str| {
+        let help = format!("{} information.", name);
+        family(&mut out, &format!("{}_info", name), &help, [(vec![(label, value)], 1)]);
+    };
+    info_metric("os", "pretty_name", &info.os);
+    info_metric("host", "hostname", &info.host);
+    info_metric("kernel", "release", &info.kernel);
+    info_metric("init", "name", &info.init);
+    info_metric("shell", "name", &info.shell);
+
+    out
+}
+
+/// The `_used_bytes` family for every sample, then the `_total_bytes` one.
+fn usage_families(out: &mut String, name: &str, what: &str, samples: &[(Labels, Usage)]) {
+    let used = samples.iter().map(|(labels, usage)| (labels.clone(), usage.used));
+    family(out, &format!("{}_used_bytes", name), &format!("{} in use, in bytes.", what), used);
+    let total = samples.iter().map(|(labels, usage)| (labels.clone(), usage.total));
+    family(out, &format!("{}_total_bytes", name), &format!("{} total, in bytes.", what), total);
+}
+
+/// A family with a single unlabelled sample.
+fn gauge(out: &mut String, name: &str, help: &str, value: impl Display) {
+    family(out, name, help, [(Vec::new(), value)]);
+}
+
+/// One metric family: its HELP and TYPE lines, then all of its samples
+/// together, as the exposition format requires. Nothing for no samples.
+fn family<'a, V: Display>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    samples: impl IntoIterator<Item = (Labels<'a>, V)>,
+) {
+    let mut samples = samples.into_iter().peekable();
+    if samples.peek().is_none() {
+        return;
+    }
+    let name = format!("rfetch_{}", sanitize(name));
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    for (labels, value) in samples {
+        let _ = write!(out, "{}", name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", sanitize(key), escape(value)))
+                .collect();
+            let _ = write!(out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(out, " {}", value);
+    }
+}
+
+/// Maps `name` onto `[a-zA-Z_][a-zA-Z0-9_]*`, the identifier syntax shared
+/// by metric and label names.
+fn sanitize(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::{PackageCount, Storage};
+
+    #[test]
+    fn families_group_their_samples() {
+        let usage = |used| Some(Usage { used, total: 100 });
+        let info = SystemInfo {
+            storage: vec![
+                Storage { usage: usage(40), filesystem: Some("ext4".into()), ..Storage::unavailable("/") },
+                Storage { usage: None, ..Storage::unavailable("/net") },
+                Storage { usage: usage(60), filesystem: Some("btrfs".into()), ..Storage::unavailable("/mnt/data") },
+            ],
+            packages: vec![PackageCount { source: "odd\"one\\\nhere".into(), count: 3 }],
+            ..SystemInfo::default()
+        };
+        let out = render(&info);
+        let storage: Vec<&str> = out.lines().filter(|line| line.contains("rfetch_storage_")).collect();
+        assert_eq!(
+            storage,
+            [
+                "# HELP rfetch_storage_used_bytes Filesystem space in use, in bytes.",
+                "# TYPE rfetch_storage_used_bytes gauge",
+                "rfetch_storage_used_bytes{mount=\"/\",fstype=\"ext4\"} 40",
+                "rfetch_storage_used_bytes{mount=\"/mnt/data\",fstype=\"btrfs\"} 60",
+                "# HELP rfetch_storage_total_bytes Filesystem space total, in bytes.",
+                "# TYPE rfetch_storage_total_bytes gauge",
+                "rfetch_storage_total_bytes{mount=\"/\",fstype=\"ext4\"} 100",
+                "rfetch_storage_total_bytes{mount=\"/mnt/data\",fstype=\"btrfs\"} 100",
+            ]
+        );
+        assert!(out.contains("rfetch_packages{source=\"odd\\\"one\\\\\\nhere\"} 3\n"));
+        assert!(!out.contains("rfetch_memory"));
+
+        // Each family is introduced once, and its samples follow unbroken.
+        let mut seen = Vec::new();
+        let mut current = "";
+        for line in out.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let name = rest.split(' ').next().unwrap();
+                assert!(!seen.contains(&name), "`{}` is introduced twice", name);
+                seen.push(name);
+                current = name;
+            } else if !line.starts_with('#') {
+                let name = line.split(['{', ' ']).next().unwrap();
+                assert_eq!(name, current, "`{}` is outside its family", line);
+            }
+        }
+    }
+
+    #[test]
+    fn names_are_sanitized() {
+        assert_eq!(sanitize("load1"), "load1");
+        assert_eq!(sanitize("1password"), "_1password");
+        assert_eq!(sanitize("my-pkg.source"), "my_pkg_source");
+        assert_eq!(sanitize(""), "_");
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
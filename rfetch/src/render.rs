@@ -0,0 +1,589 @@
+//! The default human-readable output.
+
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    color::{self, paint},
+    config::{Align, Config, UptimeStyle},
+    fields,
+    info::{PowerSource, SystemInfo, Usage},
+    kernel,
+    template,
+    units::{self, Units},
+    width::{self, display_width, pad_right},
+};
+
+pub fn render(info: &SystemInfo, config: &Config) -> String {
+    let mut out = String::new();
+
+    let title = template::render(
+        &config.title_format,
+        &[("user", &info.user), ("host", &info.host), ("os", &info.os)],
+    );
+    let title = if config.title_lowercase { title.to_lowercase() } else { title };
+    let color = config.color.enabled();
+    let palette = config.palette();
+    if !title.is_empty() {
+        let title = match config.title_gradient {
+            Some((from, to)) if color => color::gradient(&title, from, to, color::truecolor()),
+            _ => paint(&title, &palette.title, color),
+        };
+        let _ = writeln!(out, "{}", title);
+    }
+    if config.separator {
+        // Match the title, as measured on screen; ten dashes without one.
+        let length = match display_width(&title) {
+            0 => 10,
+            n => n,
+        };
+        let dashes = "-".repeat(length);
+        let _ = writeln!(out, "{}", paint(&dashes, &palette.separator, color && !palette.separator.is_empty()));
+    }
+
+    // Output follows `config.fields` exactly; every value was collected
+    // before rendering starts.
+    let rows: Vec<(String, FieldValue)> = config
+        .fields
+        .iter()
+        .filter_map(|name| {
+            let (label, value) = lookup(name, info, config)?;
+            Some((config.label_case.apply(label), value?))
+        })
+        .collect();
+    // The label column fits the widest label shown, as measured on screen.
+    let label_width = rows.iter().map(|(label, _)| display_width(label)).max().unwrap_or(0);
+    for (label, value) in rows {
+        let label = &pad_right(&paint(&label, &palette.label, color), label_width);
+        match value {
+            FieldValue::Single(value) => push_line(&mut out, label, &value),
+            FieldValue::Multi(values) => push_lines(&mut out, label, &values),
+        }
+    }
+
+    match (config.align, width::terminal_size()) {
+        (Align::Right, Some((columns, _))) => align_right(&out, columns as usize),
+        _ => out,
+    }
+}
+
+/// Shifts the whole block right so its widest line ends at `columns`.
+/// Blocks wider than that stay where they are.
+fn align_right(out: &str, columns: usize) -> String {
+    let widest = out.lines().map(display_width).max().unwrap_or(0);
+    let Some(pad) = columns.checked_sub(widest).filter(|&pad| pad > 0) else {
+        return out.to_string();
+    };
+    let pad = " ".repeat(pad);
+    out.lines().map(|line| format!("{}{}\n", pad, line)).collect()
+}
+
+/// What one field renders as, before it is laid out next to its label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FieldValue {
+    Single(String),
+    /// The first value goes on the label's line, the rest on aligned
+    /// continuation lines. An empty list hides the field.
+    Multi(Vec<String>),
+}
+
+/// The label and value of a `fields` entry: a built-in field, or a
+/// `custom:` or `file:` one. `None` for a name that is none of those.
+fn lookup<'a>(name: &str, info: &SystemInfo, config: &'a Config) -> Option<(&'a str, Option<FieldValue>)> {
+    if let Some(custom) = config.custom_field(name) {
+        let value = info.custom.get(&custom.name).map(|output| FieldValue::Multi(output.lines().map(String::from).collect()));
+        Some((custom.label.as_str(), value))
+    } else if let Some(file) = config.file_field(name) {
+        let line = info.file_fields.get(&file.name).cloned().flatten();
+        Some((file.label.as_str(), Some(FieldValue::Single(line.unwrap_or_else(|| "N/A".to_string())))))
+    } else {
+        let def = fields::find(name)?;
+        Some((config.labels.get(def.name).map_or(def.label, String::as_str), field_value(def.name, info, config)))
+    }
+}
+
+/// A `fields` entry's value on one line, multi-line values joined with
+/// `, `. The outer `None` is for a name that isn't a field at all.
+pub fn field_text(name: &str, info: &SystemInfo, config: &Config) -> Option<Option<String>> {
+    let (_, value) = lookup(name, info, config)?;
+    Some(value.and_then(|value| match value {
+        FieldValue::Single(value) => Some(value),
+        FieldValue::Multi(values) if values.is_empty() => None,
+        FieldValue::Multi(values) => Some(values.join(", ")),
+    }))
+}
+
+/// Formats field `name`, or `None` when there is nothing to show.
+fn field_value(name: &str, info: &SystemInfo, config: &Config) -> Option<FieldValue> {
+    use FieldValue::{Multi, Single};
+
+    let color = config.color.enabled();
+    let palette = config.palette();
+    let size = |usage: Usage| format_usage(usage, config.units, config.precision);
+    let sized = |usage: Usage| with_bar(size(usage), usage, config.bar.width, config);
+    let unknown = || "unknown".to_string();
+    // In a chroot, /proc still belongs to the host.
+    let from_host = |value: String| if info.chroot { format!("{} (host)", value) } else { value };
+
+    let value = match name {
+        "os" => Single(info.os.clone()),
+        "multiplexer" => Single(info.multiplexer.clone()?),
+        "term_size" => {
+            let (columns, rows) = info.terminal_size?;
+            Single(format!("{}x{}", columns, rows))
+        }
+        "locks" => {
+            let locks = info.locks?;
+            let on: Vec<&str> = [(locks.caps, "Caps"), (locks.num, "Num"), (locks.scroll, "Scroll")]
+                .into_iter()
+                .filter_map(|(lit, name)| lit.then_some(name))
+                .collect();
+            Single(if on.is_empty() { "none".to_string() } else { on.join(" ") })
+        }
+        "host" => {
+            let host = if info.chroot { Some("chroot".to_string()) } else { None };
+            Single(host.or_else(|| info.wsl.map(|v| format!("WSL{}", v))).or_else(|| info.model.clone())?)
+        }
+        "init" => Single(from_host(info.init.clone())),
+        "kernel" => {
+            let (version, local) = kernel::split_release(&info.kernel);
+            let mut value = match config.kernel_highlight && !local.is_empty() {
+                true => format!("{}{}", version, paint(local, &palette.label, color)),
+                false => info.kernel.clone(),
+            };
+            if let Some(date) = &info.kernel_build {
+                let _ = write!(value, " (built {})", date);
+            }
+            Single(value)
+        }
+        "uptime" => {
+            let value = match config.uptime_style {
+                UptimeStyle::Elapsed => info.uptime.map(|uptime| match info.idle {
+                    Some(idle) if config.uptime_idle => {
+                        format!("{} (idle {})", format_uptime(uptime), units::format_percent(idle, config.precision))
+                    }
+                    _ => format_uptime(uptime),
+                }),
+                UptimeStyle::Absolute => info
+                    .boot_time
+                    .and_then(format_local_time)
+                    .map(|time| format!("since {}", time)),
+                UptimeStyle::Relative => info.boot_time.map(format_time_ago),
+            };
+            Single(from_host(value.unwrap_or_else(unknown)))
+        }
+        "shell" => Single(info.shell.clone()),
+        "dm" => Single(info.display_manager.clone()?),
+        "terminal" => Single(info.terminal.clone()?),
+        "term_font" => Single(info.terminal_font.clone()?),
+        "gpu" => Multi(info.gpus.iter().map(|g| g.name.clone()).collect()),
+        "memory" => Single(
+            info.memory
+                .map(|usage| with_bar(format_memory(usage, config), usage, config.bar.width, config))
+                .unwrap_or_else(unknown),
+        ),
+        "swap" => {
+            let mut values = vec![info.swap.map(sized).unwrap_or_else(unknown)];
+            for device in &info.swap_devices {
+                values.push(format!("{} ({}) {}", device.name, device.kind, size(device.usage)));
+            }
+            Multi(values)
+        }
+        "storage" => {
+            // Scaled bars need the biggest mount before drawing any of them.
+            let largest = info.storage.iter().filter_map(|s| s.usage).map(|u| u.total).max();
+            let shown = match config.storage_max {
+                0 => info.storage.len(),
+                max => max.min(info.storage.len()),
+            };
+            let mut values: Vec<String> = info.storage[..shown]
+                .iter()
+                .map(|storage| {
+                    if let Some(source) = &storage.source {
+                        let fstype = storage.filesystem.as_deref().unwrap_or("network");
+                        return format!("{} ({}: {})", storage.mount, fstype, source);
+                    }
+                    let mut value = match storage.usage {
+                        Some(usage) => format!("{} ({})", size(usage), storage.mount),
+                        None => format!("N/A ({})", storage.mount),
+                    };
+                    if !storage.notes.is_empty() {
+                        let _ = write!(value, " ({})", storage.notes.join(", "));
+                    }
+                    if let Some(inodes) = storage.inodes {
+                        let share = inodes.used as f64 * 100.0 / inodes.total as f64;
+                        let _ = write!(value, " (inodes {})", units::format_percent(share, config.precision));
+                    }
+                    match (storage.usage, largest) {
+                        (Some(usage), Some(largest)) => {
+                            let width = bar_width(usage.total, largest, config);
+                            with_bar(value, usage, width, config)
+                        }
+                        _ => value,
+                    }
+                })
+                .collect();
+            if shown < info.storage.len() {
+                values.push(format!("... and {} more", info.storage.len() - shown));
+                let total = info.storage.iter().filter_map(|s| s.usage).fold(
+                    Usage { used: 0, total: 0 },
+                    |sum, u| Usage { used: sum.used.saturating_add(u.used), total: sum.total.saturating_add(u.total) },
+                );
+                values.push(format!("{} (all {} mounts)", size(total), info.storage.len()));
+            }
+            Multi(values)
+        }
+        "scheduler" => Single(info.scheduler.as_ref()?.active.clone()),
+        "cpu_temp" => Single(format!("{:.1}°C", info.cpu_temp?)),
+        "cpu_power" => Single(format!("{:.1} W", info.cpu_power?)),
+        "power" => Single(
+            match info.power? {
+                PowerSource::Ac => "AC",
+                PowerSource::Battery => "Battery",
+            }
+            .to_string(),
+        ),
+        "thermal" => match info.throttling? {
+            true => Single(paint("THROTTLING", &palette.warning, color)),
+            false => Single("OK".to_string()),
+        },
+        "displays" if !info.displays.is_empty() => {
+            let pixels: u64 = info.displays.iter().map(|d| d.width as u64 * d.height as u64).sum();
+            Single(format!("{} ({:.1} MP total)", info.displays.len(), pixels as f64 / 1e6))
+        }
+        "monitor" => {
+            // The built-in panel when there is one, else the first output.
+            let display = info.displays.iter().find(|d| d.connector.starts_with("eDP")).or(info.displays.first())?;
+            let mut value = format!("{}x{}", display.width, display.height);
+            if let Some((inches, dpi)) = display.physical() {
+                let inches = format!("{:.1}", inches);
+                let _ = write!(value, " ({}\", {:.0} DPI)", inches.trim_end_matches(".0"), dpi);
+            }
+            Single(value)
+        }
+        "opengl" => Single(info.opengl.clone()?),
+        "vulkan" => Single(info.vulkan.clone()?),
+        "scale" => Single(info.scale.clone()?),
+        "microarch" => Single(info.microarch.clone()?),
+        "topology" => {
+            let topology = info.topology?;
+            // A plain single-socket, single-node machine has nothing to tell.
+            if topology.sockets.unwrap_or(1) <= 1 && topology.numa_nodes.unwrap_or(1) <= 1 {
+                return None;
+            }
+            let plural = |n: usize, what: &str| format!("{} {}{}", n, what, if n == 1 { "" } else { "s" });
+            let parts: Vec<String> = [(topology.sockets, "socket"), (topology.numa_nodes, "NUMA node")]
+                .into_iter()
+                .filter_map(|(count, what)| Some(plural(count?, what)))
+                .collect();
+            Single(parts.join(", "))
+        }
+        "governor" => Single(info.governor.clone()?),
+        "cmdline" => Single(info.cmdline.clone()?),
+        "notifications" => Single(info.notifications.clone()?),
+        "compositor" => Single(info.compositor.clone()?),
+        "gateway" => Single(info.gateway.clone()?),
+        "dns" if !info.dns.is_empty() => Single(info.dns.join(", ")),
+        "network" => {
+            let network = info.network.as_ref()?;
+            let mut value = format!("{} {}", network.interface, network.kind);
+            if let Some(ssid) = &network.ssid {
+                let _ = write!(value, " \"{}\"", ssid);
+            }
+            if let Some(dbm) = network.signal_dbm {
+                let _ = write!(value, " ({} dBm)", dbm);
+            }
+            Single(value)
+        }
+        "security" => Single(info.security.clone()?),
+        "packages" if !info.packages.is_empty() => {
+            let counts: Vec<String> = info.packages.iter().map(|p| format!("{} ({})", p.count, p.source)).collect();
+            Single(counts.join(", "))
+        }
+        "services" => {
+            let services = info.services?;
+            let failed = format!("{} failed", services.failed);
+            let failed = if services.failed > 0 { paint(&failed, &palette.warning, color) } else { failed };
+            Single(format!("{} running, {}", services.running, failed))
+        }
+        "updates" => Single(match info.updates {
+            Some(count) => format!("{} available", count),
+            None => "N/A".into(),
+        }),
+        "version" => Single(crate::version()),
+        "git" => {
+            let git = info.git.as_ref()?;
+            Single(match git.dirty {
+                Some(true) => format!("{} ✗", git.branch),
+                _ => git.branch.clone(),
+            })
+        }
+        "ram_modules" if !info.memory_modules.is_empty() => {
+            let groups: Vec<String> = info
+                .memory_modules
+                .iter()
+                .map(|modules| {
+                    // dmidecode's own notation: powers of 1024, written GB.
+                    let mib = modules.size / (1024 * 1024);
+                    let mut value = if mib >= 1024 && mib % 1024 == 0 {
+                        format!("{}x {}GB", modules.count, mib / 1024)
+                    } else {
+                        format!("{}x {}MB", modules.count, mib)
+                    };
+                    if let Some(kind) = &modules.kind {
+                        let _ = write!(value, " {}", kind);
+                        if let Some(speed) = modules.speed {
+                            let _ = write!(value, "-{}", speed);
+                        }
+                    }
+                    value
+                })
+                .collect();
+            Single(groups.join(", "))
+        }
+        "pressure" => {
+            let pressure = info.pressure?;
+            let parts: Vec<String> = [("cpu", pressure.cpu), ("mem", pressure.memory)]
+                .into_iter()
+                .filter_map(|(name, avg)| Some(format!("{} {}", name, units::format_percent(avg?, config.precision))))
+                .collect();
+            Single(parts.join(", "))
+        }
+        "files" => {
+            let files = info.files?;
+            Single(format!("{} / {}", files.open, files.max))
+        }
+        "entropy" => {
+            let entropy = info.entropy?;
+            // Older kernels block /dev/random below this.
+            Single(paint(&entropy.to_string(), &palette.warning, color && entropy < LOW_ENTROPY))
+        }
+        "modules" => {
+            let modules = info.kernel_modules.as_ref()?;
+            let mut values = vec![modules.count.to_string()];
+            for (name, bytes) in &modules.largest {
+                values.push(format!("{} {}", name, units::format_size(*bytes, config.units, config.precision)));
+            }
+            Multi(values)
+        }
+        "fan" => Multi(
+            info.fans
+                .iter()
+                .map(|fan| match &fan.label {
+                    Some(label) => format!("{} {} RPM", label, fan.rpm),
+                    None => format!("{} RPM", fan.rpm),
+                })
+                .collect(),
+        ),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Entropy pool size, in bits, under which the entropy field is painted
+/// as a warning.
+const LOW_ENTROPY: u64 = 256;
+
+/// Writes a line for `label`, already padded to the label column.
+fn push_line(out: &mut String, label: &str, value: &str) {
+    let _ = writeln!(out, "{}: {}", label, value);
+}
+
+/// Writes `values[0]` on the label line and any further values on
+/// continuation lines aligned under it. Nothing is written for no values.
+fn push_lines(out: &mut String, label: &str, values: &[String]) {
+    let mut values = values.iter();
+    if let Some(first) = values.next() {
+        push_line(out, label, first);
+    }
+    for value in values {
+        let _ = writeln!(out, "{}  {}", " ".repeat(display_width(label)), value);
+    }
+}
+
+/// Appends a `[####------]` bar of `width` cells when bars are enabled.
+fn with_bar(value: String, usage: Usage, width: usize, config: &Config) -> String {
+    if !config.bar.enabled || usage.total == 0 || width == 0 {
+        return value;
+    }
+    let ratio = (usage.used as f64 / usage.total as f64).clamp(0.0, 1.0);
+    let filled = (ratio * width as f64).round() as usize;
+    let percent = ratio * 100.0;
+    let level = if percent > config.bar.critical as f64 {
+        2
+    } else if percent >= config.bar.warn as f64 {
+        1
+    } else {
+        0
+    };
+    let paint_fill = config.bar.color && filled > 0 && config.color.enabled();
+    let fill = paint(&"#".repeat(filled), &config.palette().bar[level], paint_fill);
+    format!("{} [{}{}]", value, fill, "-".repeat(width - filled))
+}
+
+/// Bar width for a mount of `total` bytes: the configured width, or with
+/// `scale` a share of it proportional to the largest mount shown, never
+/// shorter than `min_width` so small mounts stay visible.
+fn bar_width(total: u64, largest: u64, config: &Config) -> usize {
+    let bar = &config.bar;
+    if !bar.scale || largest == 0 {
+        return bar.width;
+    }
+    let scaled = (bar.width as f64 * total as f64 / largest as f64).round() as usize;
+    scaled.clamp(bar.min_width.min(bar.width), bar.width)
+}
+
+/// `3h 12m`, with days in front once there are any: `4d 3h 12m`.
+fn format_uptime(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+    match hours / 24 {
+        0 => format!("{}h {}m", hours, minutes % 60),
+        days => format!("{}d {}h {}m", days, hours % 24, minutes % 60),
+    }
+}
+
+/// `YYYY-MM-DD HH:MM` in the local timezone.
+fn format_local_time(timestamp: u64) -> Option<String> {
+    let time = libc::time_t::try_from(timestamp).ok()?;
+    // SAFETY: `tm` is plain data and localtime_r only writes into it.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return None;
+    }
+    Some(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min
+    ))
+}
+
+/// `3 weeks ago`, `1 day ago`, ... down to `just now` under a minute.
+fn format_time_ago(timestamp: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let elapsed = now.saturating_sub(timestamp);
+
+    const UNITS: &[(u64, &str)] = &[(7 * 86400, "week"), (86400, "day"), (3600, "hour"), (60, "minute")];
+    for &(size, name) in UNITS {
+        let count = elapsed / size;
+        if count > 0 {
+            let plural = if count == 1 { "" } else { "s" };
+            return format!("{} {}{} ago", count, name, plural);
+        }
+    }
+    "just now".into()
+}
+
+fn format_usage(usage: Usage, units: Units, precision: usize) -> String {
+    units::format_usage(usage.used, usage.total, units, precision)
+}
+
+/// The memory sizes, in `memory_unit` when set, then the share in use
+/// with `memory_percent`.
+fn format_memory(usage: Usage, config: &Config) -> String {
+    let mut value = match config.memory_unit {
+        Some(unit) => units::format_usage_in(usage.used, usage.total, unit, config.precision),
+        None => format_usage(usage, config.units, config.precision),
+    };
+    if config.memory_percent && usage.total > 0 {
+        let share = usage.used as f64 * 100.0 / usage.total as f64;
+        let _ = write!(value, " ({})", units::format_percent(share, config.precision));
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorMode;
+
+    #[test]
+    fn uptime_formatting() {
+        assert_eq!(format_uptime(0), "0h 0m");
+        assert_eq!(format_uptime(3 * 3600 + 12 * 60 + 59), "3h 12m");
+        assert_eq!(format_uptime(4 * 86400 + 3 * 3600 + 12 * 60), "4d 3h 12m");
+        assert_eq!(format_uptime(u64::MAX), "213503982334601d 7h 0m");
+    }
+
+    #[test]
+    fn memory_on_small_boards() {
+        let mib = 1024 * 1024;
+        let usage = Usage { used: 480 * mib, total: 512 * mib };
+        let config = Config { memory_percent: true, ..Config::default() };
+        assert_eq!(format_memory(usage, &config), "480.0 MiB / 512.0 MiB (93.8%)");
+
+        let usage = Usage { used: 1536 * mib, total: 4096 * mib };
+        let config = Config { memory_unit: Some(units::Unit::parse("MiB").unwrap()), ..config };
+        assert_eq!(format_memory(usage, &config), "1536.0 MiB / 4096.0 MiB (37.5%)");
+    }
+
+    #[test]
+    fn right_alignment_shifts_the_block() {
+        let out = "me@box\n------\nOS      : Arch Linux\n";
+        assert_eq!(align_right(out, 24), "    me@box\n    ------\n    OS      : Arch Linux\n");
+        assert_eq!(align_right(out, 12), out);
+    }
+
+    #[test]
+    fn lines_follow_the_configured_order() {
+        // Filled in an order unrelated to either the struct or `fields`.
+        let info = SystemInfo {
+            uptime: Some(3600),
+            shell: "zsh".into(),
+            kernel: "6.9.1".into(),
+            init: "systemd".into(),
+            os: "Arch Linux".into(),
+            ..SystemInfo::default()
+        };
+        let config = Config {
+            title_format: String::new(),
+            separator: false,
+            color: ColorMode::Never,
+            fields: ["shell", "os", "uptime", "kernel", "init"].map(String::from).to_vec(),
+            ..Config::default()
+        };
+        let labels: Vec<String> = render(&info, &config)
+            .lines()
+            .map(|line| line.split(':').next().unwrap().trim_end().to_string())
+            .collect();
+        assert_eq!(labels, ["Shell", "OS", "Uptime", "Kernel", "Init"]);
+    }
+
+    #[test]
+    fn labels_pad_to_the_widest_shown() {
+        let info = SystemInfo { os: "Arch Linux".into(), kernel: "6.9.1".into(), ..SystemInfo::default() };
+        let config = Config {
+            title_format: String::new(),
+            separator: false,
+            color: ColorMode::Never,
+            fields: vec!["os".into(), "kernel".into()],
+            labels: [("os".to_string(), "系统".to_string())].into(),
+            ..Config::default()
+        };
+        assert_eq!(render(&info, &config), "系统  : Arch Linux\nKernel: 6.9.1\n");
+
+        // Escapes in painted labels take no room.
+        let config = Config { color: ColorMode::Always, ..config };
+        let label = |text: &str| paint(text, &config.palette().label, true);
+        assert_eq!(render(&info, &config), format!("{}  : Arch Linux\n{}: 6.9.1\n", label("系统"), label("Kernel")));
+    }
+
+    #[test]
+    fn multi_values_continue_under_the_label() {
+        let mut out = String::new();
+        push_lines(&mut out, "GPU     ", &["AMD Radeon RX 6600".to_string(), "Intel UHD 770".to_string()]);
+        assert_eq!(out, "GPU     : AMD Radeon RX 6600\n          Intel UHD 770\n");
+
+        let mut out = String::new();
+        push_lines(&mut out, "GPU", &[]);
+        assert_eq!(out, "");
+    }
+}
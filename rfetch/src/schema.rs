@@ -0,0 +1,361 @@
+//! `rfetch schema`: a JSON Schema (draft 2020-12) describing the output of
+//! `--format json`, for tooling that validates or generates types from it.
+//!
+//! The schema is written out by hand next to [`json::to_json`] rather than
+//! generated, since schemars and serde aren't dependencies. Any key added
+//! there needs its entry here; the tests fail on a key either side lacks,
+//! checked against a fully populated `SystemInfo`.
+
+use crate::json::{self, Json};
+
+pub fn render() -> String {
+    json::pretty(&schema())
+}
+
+fn schema() -> Json {
+    let usage = || object(vec![("used_bytes", integer()), ("total_bytes", integer())]);
+    let body = object(vec![
+        ("user", string()),
+        ("host", string()),
+        ("os", string()),
+        ("wsl", nullable(integer())),
+        ("chroot", boolean()),
+        ("model", nullable(string())),
+        ("init", string()),
+        ("kernel", string()),
+        ("kernel_commit", nullable(string())),
+        ("kernel_build_date", nullable(string())),
+        ("uptime_seconds", nullable(integer())),
+        ("idle_percent", nullable(number())),
+        ("boot_time", nullable(integer())),
+        ("shell", string()),
+        ("display_manager", nullable(string())),
+        ("terminal", nullable(string())),
+        ("terminal_font", nullable(string())),
+        ("multiplexer", nullable(string())),
+        ("terminal_size", nullable(object(vec![("columns", integer()), ("rows", integer())]))),
+        ("locks", nullable(object(vec![("caps", boolean()), ("num", boolean()), ("scroll", boolean())]))),
+        (
+            "gpus",
+            array(object(vec![
+                ("name", string()),
+                ("pci", string()),
+                ("kind", one_of(&["integrated", "discrete", "unknown"])),
+                ("boot_vga", boolean()),
+            ])),
+        ),
+        ("memory", nullable(usage())),
+        (
+            "memory_modules",
+            array(object(vec![
+                ("count", integer()),
+                ("size_bytes", integer()),
+                ("type", nullable(string())),
+                ("speed_mts", nullable(integer())),
+            ])),
+        ),
+        ("swap", nullable(usage())),
+        (
+            "swap_devices",
+            array(object(vec![
+                ("name", string()),
+                ("kind", string()),
+                ("used_bytes", integer()),
+                ("total_bytes", integer()),
+            ])),
+        ),
+        (
+            "storage",
+            array(object(vec![
+                ("mount", string()),
+                ("used_bytes", nullable(integer())),
+                ("total_bytes", nullable(integer())),
+                ("filesystem", nullable(string())),
+                ("notes", array(string())),
+                ("source", nullable(string())),
+                ("inodes_used", nullable(integer())),
+                ("inodes_total", nullable(integer())),
+            ])),
+        ),
+        (
+            "scheduler",
+            nullable(object(vec![("device", string()), ("active", string()), ("available", array(string()))])),
+        ),
+        ("services", nullable(object(vec![("running", integer()), ("failed", integer())]))),
+        ("packages", array(object(vec![("source", string()), ("count", integer())]))),
+        ("updates", nullable(integer())),
+        ("cpu_temp_celsius", nullable(number())),
+        ("cpu_power_watts", nullable(number())),
+        ("power", nullable(one_of(&["ac", "battery"]))),
+        ("throttling", nullable(boolean())),
+        (
+            "displays",
+            array(object(vec![
+                ("connector", string()),
+                ("width", integer()),
+                ("height", integer()),
+                ("width_mm", nullable(integer())),
+                ("height_mm", nullable(integer())),
+            ])),
+        ),
+        ("opengl", nullable(string())),
+        ("vulkan", nullable(string())),
+        ("scale", nullable(string())),
+        ("microarch", nullable(string())),
+        ("topology", nullable(object(vec![("sockets", nullable(integer())), ("numa_nodes", nullable(integer()))]))),
+        ("governor", nullable(string())),
+        ("cmdline", nullable(string())),
+        ("notifications", nullable(string())),
+        ("compositor", nullable(string())),
+        ("security", nullable(string())),
+        ("files", nullable(object(vec![("open", integer()), ("max", integer())]))),
+        ("entropy_bits", nullable(integer())),
+        (
+            "kernel_modules",
+            nullable(object(vec![
+                ("count", integer()),
+                ("largest", array(object(vec![("name", string()), ("size_bytes", integer())]))),
+            ])),
+        ),
+        ("gateway", nullable(string())),
+        ("dns_servers", array(string())),
+        (
+            "network",
+            nullable(object(vec![
+                ("interface", string()),
+                ("kind", one_of(&["wired", "wifi", "virtual"])),
+                ("ssid", nullable(string())),
+                ("signal_dbm", nullable(number())),
+            ])),
+        ),
+        ("fans", array(object(vec![("label", nullable(string())), ("rpm", integer())]))),
+        ("git", nullable(object(vec![("branch", string()), ("dirty", nullable(boolean()))]))),
+        ("pressure", nullable(object(vec![("cpu", nullable(number())), ("memory", nullable(number()))]))),
+        ("loadavg", nullable(with(array(number()), vec![("minItems", 3usize.into()), ("maxItems", 3usize.into())]))),
+        // Keyed by `[[custom]]` name, so the keys aren't known up front.
+        ("custom", with(kind("object"), vec![("additionalProperties", string())])),
+        ("file_fields", with(kind("object"), vec![("additionalProperties", nullable(string()))])),
+    ]);
+    with(
+        json::object(vec![
+            ("$schema", "https://json-schema.org/draft/2020-12/schema".into()),
+            ("title", "rfetch".into()),
+        ]),
+        match body {
+            Json::Object(entries) => entries,
+            _ => Vec::new(),
+        },
+    )
+}
+
+fn kind(name: &str) -> Json {
+    json::object(vec![("type", name.into())])
+}
+
+fn string() -> Json {
+    kind("string")
+}
+
+fn integer() -> Json {
+    with(kind("integer"), vec![("minimum", 0usize.into())])
+}
+
+fn number() -> Json {
+    kind("number")
+}
+
+fn boolean() -> Json {
+    kind("boolean")
+}
+
+fn one_of(values: &[&str]) -> Json {
+    json::object(vec![("enum", Json::Array(values.iter().map(|&v| v.into()).collect()))])
+}
+
+fn array(items: Json) -> Json {
+    json::object(vec![("type", "array".into()), ("items", items)])
+}
+
+/// A closed object: every key listed, always present, nothing else.
+fn object(properties: Vec<(&str, Json)>) -> Json {
+    let required = properties.iter().map(|(key, _)| Json::from(*key)).collect();
+    json::object(vec![
+        ("type", "object".into()),
+        ("properties", json::object(properties)),
+        ("required", Json::Array(required)),
+        ("additionalProperties", false.into()),
+    ])
+}
+
+fn nullable(schema: Json) -> Json {
+    json::object(vec![("anyOf", Json::Array(vec![schema, kind("null")]))])
+}
+
+/// `schema` with extra keywords appended; `schema` must be an object.
+fn with<K: ToString>(schema: Json, extra: Vec<(K, Json)>) -> Json {
+    match schema {
+        Json::Object(mut entries) => {
+            entries.extend(extra.into_iter().map(|(k, v)| (k.to_string(), v)));
+            Json::Object(entries)
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::*;
+
+    /// Every `Option` set and every list non-empty, so that each key
+    /// `to_json` can write shows up, nested ones included.
+    fn populated() -> SystemInfo {
+        let usage = Usage { used: 1, total: 2 };
+        SystemInfo {
+            user: "me".into(),
+            host: "box".into(),
+            os: "Arch Linux".into(),
+            wsl: Some(2),
+            chroot: true,
+            model: Some("ThinkPad X1 Carbon Gen 9".into()),
+            init: "systemd".into(),
+            kernel: "6.10.0-rc3-00042-gabc1234".into(),
+            kernel_build: Some("2024-05-20".into()),
+            uptime: Some(3600),
+            idle: Some(80.0),
+            boot_time: Some(1_716_200_000),
+            shell: "zsh".into(),
+            display_manager: Some("sddm".into()),
+            terminal: Some("kitty".into()),
+            terminal_font: Some("Iosevka 12".into()),
+            multiplexer: Some("tmux".into()),
+            terminal_size: Some((80, 24)),
+            locks: Some(Locks { caps: true, num: true, scroll: false }),
+            gpus: vec![Gpu { name: "AMD Radeon RX 6600".into(), pci: "0000:03:00.0".into(), kind: GpuKind::Discrete, boot_vga: true }],
+            memory: Some(usage),
+            memory_modules: vec![MemoryModules { count: 2, size: 8 << 30, kind: Some("DDR4".into()), speed: Some(3200) }],
+            swap: Some(usage),
+            swap_devices: vec![SwapDevice { name: "/dev/zram0".into(), kind: "zram".into(), usage }],
+            storage: vec![Storage {
+                mount: "/".into(),
+                usage: Some(usage),
+                filesystem: Some("btrfs".into()),
+                notes: vec!["compressed".into()],
+                source: Some("/dev/nvme0n1p2".into()),
+                inodes: Some(usage),
+            }],
+            scheduler: Some(Scheduler { device: "nvme0n1".into(), active: "none".into(), available: vec!["none".into()] }),
+            services: Some(Services { running: 40, failed: 1 }),
+            packages: vec![PackageCount { source: "pacman".into(), count: 900 }],
+            updates: Some(3),
+            opengl: Some("4.6 (AMD Radeon RX 6600)".into()),
+            vulkan: Some("1.3.246 (AMD Radeon RX 6600)".into()),
+            scale: Some("2x".into()),
+            microarch: Some("AMD (Zen 3)".into()),
+            topology: Some(Topology { sockets: Some(1), numa_nodes: Some(1) }),
+            cpu_temp: Some(45.5),
+            cpu_power: Some(12.5),
+            power: Some(PowerSource::Battery),
+            throttling: Some(false),
+            displays: vec![Display { connector: "eDP-1".into(), width: 2560, height: 1600, size_mm: Some((300, 190)) }],
+            governor: Some("powersave".into()),
+            cmdline: Some("quiet".into()),
+            notifications: Some("dunst".into()),
+            compositor: Some("Hyprland 0.41.2".into()),
+            security: Some("SELinux (Enforcing)".into()),
+            fans: vec![Fan { label: Some("cpu".into()), rpm: 1200 }],
+            kernel_modules: Some(KernelModules { count: 120, largest: vec![("amdgpu".into(), 1 << 20)] }),
+            files: Some(FileHandles { open: 1000, max: 100_000 }),
+            entropy: Some(256),
+            gateway: Some("192.168.1.1".into()),
+            dns: vec!["1.1.1.1".into()],
+            network: Some(Network {
+                interface: "wlan0".into(),
+                kind: "wifi".into(),
+                ssid: Some("home".into()),
+                signal_dbm: Some(-52),
+            }),
+            git: Some(GitRepo { branch: "main".into(), dirty: Some(true) }),
+            pressure: Some(Pressure { cpu: Some(0.5), memory: Some(0.25) }),
+            loadavg: Some([0.5, 0.25, 0.1]),
+            custom: [("vpn".to_string(), "wg0".to_string())].into(),
+            file_fields: [("motd".to_string(), Some("hello".to_string()))].into(),
+        }
+    }
+
+    fn keyword<'s>(schema: &'s Json, name: &str) -> Option<&'s Json> {
+        match schema {
+            Json::Object(entries) => entries.iter().find(|(key, _)| key == name).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn names(entries: impl IntoIterator<Item = String>) -> Vec<String> {
+        let mut names: Vec<String> = entries.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    /// Walks `value` and `schema` together, failing at the first key or
+    /// type they disagree on.
+    fn check(value: &Json, schema: &Json, path: &str) {
+        if let Some(Json::Array(options)) = keyword(schema, "anyOf") {
+            assert!(!matches!(value, Json::Null), "`{}` is null; fill it in", path);
+            return check(value, &options[0], path);
+        }
+        if let Some(Json::Array(allowed)) = keyword(schema, "enum") {
+            let known = allowed.iter().any(|v| matches!((v, value), (Json::Str(a), Json::Str(b)) if a == b));
+            assert!(known, "`{}`: {:?} is not in the enum", path, value);
+            return;
+        }
+        let Some(Json::Str(kind)) = keyword(schema, "type") else {
+            panic!("`{}` has no type in the schema", path);
+        };
+        match (kind.as_str(), value) {
+            ("string", Json::Str(_)) | ("integer" | "number", Json::Int(_)) | ("number", Json::Float(_)) => {}
+            ("boolean", Json::Bool(_)) => {}
+            ("array", Json::Array(items)) => {
+                assert!(!items.is_empty(), "`{}` is empty; fill it in", path);
+                let items_schema = keyword(schema, "items").unwrap();
+                for (i, item) in items.iter().enumerate() {
+                    check(item, items_schema, &format!("{}[{}]", path, i));
+                }
+            }
+            ("object", Json::Object(entries)) => match keyword(schema, "properties") {
+                Some(Json::Object(properties)) => {
+                    let keys = names(entries.iter().map(|(key, _)| key.clone()));
+                    let listed = names(properties.iter().map(|(key, _)| key.clone()));
+                    let unlisted: Vec<&String> = keys.iter().filter(|key| !listed.contains(key)).collect();
+                    assert!(unlisted.is_empty(), "`{}` writes {:?}, which the schema lacks", path, unlisted);
+                    let unwritten: Vec<&String> = listed.iter().filter(|key| !keys.contains(key)).collect();
+                    assert!(unwritten.is_empty(), "`{}` never writes {:?} from the schema", path, unwritten);
+                    let Some(Json::Array(required)) = keyword(schema, "required") else {
+                        panic!("`{}` has no required list", path);
+                    };
+                    let required = required.iter().map(|key| match key {
+                        Json::Str(key) => key.clone(),
+                        other => panic!("`{}`: {:?} in required", path, other),
+                    });
+                    assert_eq!(keys, names(required), "required of `{}`", path);
+                    for (key, value) in entries {
+                        let (_, property) = properties.iter().find(|(name, _)| name == key).unwrap();
+                        check(value, property, &format!("{}.{}", path, key));
+                    }
+                }
+                _ => {
+                    assert!(!entries.is_empty(), "`{}` is empty; fill it in", path);
+                    let values = keyword(schema, "additionalProperties").unwrap();
+                    for (key, value) in entries {
+                        check(value, values, &format!("{}.{}", path, key));
+                    }
+                }
+            },
+            (kind, value) => panic!("`{}`: {:?} is not a {}", path, value, kind),
+        }
+    }
+
+    #[test]
+    fn schema_matches_the_json_output() {
+        check(&json::to_json(&populated()), &schema(), "$");
+    }
+}
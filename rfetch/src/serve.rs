@@ -0,0 +1,69 @@
+//! `--serve <addr>`: a tiny HTTP endpoint answering `GET /` with the fetch
+//! as JSON, for homelab dashboards.
+//!
+//! Requests are handled one at a time on the calling thread and there is
+//! no authentication or TLS: bind it to a trusted interface, not the open
+//! internet.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+use crate::{config::Config, info::SystemInfo, json};
+
+/// Serves forever. `refresh` is called before each response so volatile
+/// fields (memory, uptime, ...) are current; everything else is collected
+/// once up front.
+pub fn serve(addr: &str, config: &Config, mut info: SystemInfo, refresh: fn(&mut SystemInfo, &Config)) -> Result<(), String> {
+    let listener = TcpListener::bind(addr).map_err(|e| format!("cannot listen on {}: {}", addr, e))?;
+    eprintln!("rfetch: serving on http://{}", listener.local_addr().map_err(|e| e.to_string())?);
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        // A misbehaving client only loses its own request.
+        let _ = handle(stream, config, &mut info, refresh);
+    }
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, config: &Config, info: &mut SystemInfo, refresh: fn(&mut SystemInfo, &Config)) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers; nothing in them matters to us.
+    let mut header = String::new();
+    let mut header_bytes = 0;
+    while reader.read_line(&mut header)? > 2 && header_bytes < 16 * 1024 {
+        header_bytes += header.len();
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+
+    let (status, body) = match (method, path) {
+        ("GET", "/") => {
+            refresh(info, config);
+            ("200 OK", json::render(info))
+        }
+        ("GET", _) => ("404 Not Found", "{\"error\": \"not found\"}\n".to_string()),
+        _ => ("405 Method Not Allowed", "{\"error\": \"method not allowed\"}\n".to_string()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,135 @@
+//! `--summary`: the fetch as a single line for tmux, polybar or waybar,
+//! e.g. `6.2G/15.5G RAM | 40.0% / | up 3h 12m`. Each `[summary] items`
+//! entry is a template over field names and the compact tokens below;
+//! the items are joined with `delimiter`.
+
+use crate::{
+    color::ColorMode,
+    config::Config,
+    info::{SystemInfo, Usage},
+    render, template, units,
+};
+
+/// Compact tokens beyond the field names, and the field each comes from.
+/// `disk_*` describe the first storage mount, `/` by default.
+const TOKENS: &[(&str, &str)] = &[
+    ("mem_used", "memory"),
+    ("mem_total", "memory"),
+    ("mem_percent", "memory"),
+    ("swap_used", "swap"),
+    ("swap_total", "swap"),
+    ("swap_percent", "swap"),
+    ("disk_used", "storage"),
+    ("disk_total", "storage"),
+    ("disk_percent", "storage"),
+    ("disk_mount", "storage"),
+];
+
+/// The fields the items use, to collect in place of `fields`.
+pub fn fields(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for token in config.summary.items.iter().flat_map(|item| template::tokens(item)) {
+        let name = match TOKENS.iter().find(|(name, _)| *name == token) {
+            Some((_, field)) => field,
+            None if config.check_field(token).is_ok() => token,
+            // Left in the output as typed, like any unknown token.
+            None => continue,
+        };
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+pub fn render(info: &SystemInfo, config: &Config) -> String {
+    // Status bars take plain text.
+    let mut config = config.clone();
+    config.color = ColorMode::Never;
+
+    let items: Vec<String> = config
+        .summary
+        .items
+        .iter()
+        .filter_map(|item| {
+            let mut vars = Vec::new();
+            for token in template::tokens(item) {
+                match token_value(token, info, &config) {
+                    // A token with nothing to show drops its whole item, so
+                    // "up {uptime}" doesn't leave a bare "up".
+                    Some(value) => vars.push((token, value?)),
+                    None => continue,
+                }
+            }
+            let vars: Vec<(&str, &str)> = vars.iter().map(|(name, value)| (*name, value.as_str())).collect();
+            Some(template::render(item, &vars))
+        })
+        .collect();
+    format!("{}\n", items.join(&config.summary.delimiter))
+}
+
+/// The outer `None` is for a token that is neither a field nor in
+/// [`TOKENS`]; the inner one for a value that wasn't found.
+fn token_value(token: &str, info: &SystemInfo, config: &Config) -> Option<Option<String>> {
+    let disk = info.storage.first();
+    let value = match token {
+        "mem_used" => info.memory.map(|u| sizes(u, config).0),
+        "mem_total" => info.memory.map(|u| sizes(u, config).1),
+        "mem_percent" => info.memory.and_then(|u| percent(u, config)),
+        "swap_used" => info.swap.map(|u| sizes(u, config).0),
+        "swap_total" => info.swap.map(|u| sizes(u, config).1),
+        "swap_percent" => info.swap.and_then(|u| percent(u, config)),
+        "disk_used" => disk.and_then(|s| s.usage).map(|u| sizes(u, config).0),
+        "disk_total" => disk.and_then(|s| s.usage).map(|u| sizes(u, config).1),
+        "disk_percent" => disk.and_then(|s| s.usage).and_then(|u| percent(u, config)),
+        "disk_mount" => disk.map(|s| s.mount.clone()),
+        _ => return render::field_text(token, info, config),
+    };
+    Some(value)
+}
+
+/// Used and total in the one unit the human output would pick for both,
+/// written `6.2G` rather than `6.2 GiB` to fit a status bar.
+fn sizes(usage: Usage, config: &Config) -> (String, String) {
+    let text = units::format_usage(usage.used, usage.total, config.units, config.precision);
+    let compact = |size: &str| match size.split_once(' ') {
+        Some((number, unit)) => format!("{}{}", number, &unit[..1]),
+        None => size.to_string(),
+    };
+    let (used, total) = text.split_once(" / ").unwrap_or((&text, ""));
+    (compact(used), compact(total))
+}
+
+fn percent(usage: Usage, config: &Config) -> Option<String> {
+    let share = usage.used as f64 * 100.0 / usage.total as f64;
+    (usage.total > 0).then(|| units::format_percent(share, config.precision))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::SummaryConfig, info::Storage};
+
+    #[test]
+    fn items_and_delimiter() {
+        let info = SystemInfo {
+            memory: Some(Usage { used: 512 << 20, total: 16 << 30 }),
+            storage: vec![Storage { usage: Some(Usage { used: 40, total: 100 }), ..Storage::unavailable("/") }],
+            ..SystemInfo::default()
+        };
+        let config = Config {
+            summary: SummaryConfig {
+                items: vec![
+                    "{mem_used}/{mem_total} RAM".into(),
+                    "{disk_percent} {disk_mount}".into(),
+                    "swap {swap_percent}".into(),
+                    "{nope}".into(),
+                ],
+                delimiter: " | ".into(),
+            },
+            ..Config::default()
+        };
+        assert_eq!(render(&info, &config), "0.5G/16.0G RAM | 40.0% / | {nope}\n");
+        assert_eq!(fields(&config), ["memory", "storage", "swap"]);
+    }
+}
@@ -0,0 +1,91 @@
+//! Expansion of `{token}` placeholders in user-supplied format strings.
+
+/// The names of the `{name}` tokens in `format`, in order.
+pub fn tokens(format: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = format;
+    while let Some(i) = rest.find(['{', '}']) {
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            rest = &tail[2..];
+        } else if tail.starts_with('{')
+            && let Some(end) = tail.find('}')
+        {
+            names.push(&tail[1..end]);
+            rest = &tail[end + 1..];
+        } else {
+            rest = &tail[1..];
+        }
+    }
+    names
+}
+
+/// Replaces every `{name}` in `format` with the matching value from `vars`.
+/// Unknown tokens are kept verbatim so typos stay visible, and `{{` / `}}`
+/// produce literal braces.
+pub fn render(format: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(format.len());
+    let mut rest = format;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        if tail.starts_with('{')
+            && let Some(end) = tail.find('}')
+        {
+            let name = &tail[1..end];
+            match vars.iter().find(|(key, _)| *key == name) {
+                Some((_, value)) => out.push_str(value),
+                None => out.push_str(&tail[..=end]),
+            }
+            rest = &tail[end + 1..];
+            continue;
+        }
+
+        out.push_str(&tail[..1]);
+        rest = &tail[1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VARS: &[(&str, &str)] = &[("user", "me"), ("host", "box")];
+
+    #[test]
+    fn tokens_expand() {
+        assert_eq!(render("{user}@{host}", VARS), "me@box");
+        assert_eq!(render("{user}{host}", VARS), "mebox");
+        assert_eq!(tokens("{user}{host}"), ["user", "host"]);
+        assert_eq!(render("", VARS), "");
+        assert!(tokens("").is_empty());
+    }
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        assert_eq!(render("{{user}} is {user}", VARS), "{user} is me");
+        assert_eq!(render("}}{{", VARS), "}{");
+        assert_eq!(tokens("{{user}} {host}"), ["host"]);
+    }
+
+    #[test]
+    fn odd_braces_stay_as_typed() {
+        assert_eq!(render("{nope}@{host}", VARS), "{nope}@box");
+        assert_eq!(tokens("{nope}@{host}"), ["nope", "host"]);
+        assert_eq!(render("{user", VARS), "{user");
+        assert!(tokens("{user").is_empty());
+        assert_eq!(render("user} {host}", VARS), "user} box");
+        assert_eq!(tokens("user} {host}"), ["host"]);
+    }
+}
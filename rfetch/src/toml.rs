@@ -0,0 +1,471 @@
+//! A small parser for the subset of TOML the config file uses: tables,
+//! arrays of tables, dotted keys, strings, integers, floats, booleans,
+//! arrays and inline tables.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+pub type Table = BTreeMap<String, Value>;
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Boolean(_) => "boolean",
+            Value::Array(_) => "array",
+            Value::Table(_) => "table",
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Table, String> {
+    let mut p = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut root = Table::new();
+    let mut current: Vec<String> = Vec::new();
+    // Tables opened with a `[header]`, which may each appear only once.
+    let mut defined: BTreeSet<Vec<String>> = BTreeSet::new();
+
+    loop {
+        p.skip_blank();
+        // Errors found after the end of a line still point at it.
+        let line = p.line;
+        match p.peek() {
+            None => break,
+            Some('[') => {
+                p.bump();
+                let array = p.eat('[');
+                p.skip_ws();
+                let path = p.key_path()?;
+                p.expect(']')?;
+                if array {
+                    p.expect(']')?;
+                }
+                p.end_of_line()?;
+
+                if array {
+                    let (last, parents) = path.split_last().ok_or_else(|| p.error("expected a key"))?;
+                    let parent = table_at(&mut root, parents).map_err(|e| error_at(line, &e))?;
+                    let entry = parent
+                        .entry(last.clone())
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    match entry {
+                        Value::Array(items) => items.push(Value::Table(Table::new())),
+                        _ => return Err(error_at(line, &format!("`{}` is not an array of tables", last))),
+                    }
+                    // A new element starts afresh, so its subtables can be
+                    // opened again.
+                    defined.retain(|table| !table.starts_with(&path));
+                } else {
+                    if !defined.insert(path.clone()) {
+                        return Err(error_at(line, &format!("duplicate table `[{}]`", path.join("."))));
+                    }
+                    table_at(&mut root, &path).map_err(|e| error_at(line, &e))?;
+                }
+                current = path;
+            }
+            Some(_) => {
+                let path = p.key_path()?;
+                p.expect('=')?;
+                p.skip_ws();
+                let value = p.value()?;
+                p.end_of_line()?;
+
+                let (last, parents) = path.split_last().ok_or_else(|| p.error("expected a key"))?;
+                let mut full = current.clone();
+                full.extend_from_slice(parents);
+                let table = table_at(&mut root, &full).map_err(|e| error_at(line, &e))?;
+                if table.contains_key(last) {
+                    return Err(error_at(line, &format!("duplicate key `{}`", last)));
+                }
+                table.insert(last.clone(), value);
+            }
+        }
+    }
+
+    Ok(root)
+}
+
+fn error_at(line: usize, msg: &str) -> String {
+    format!("line {}: {}", line, msg)
+}
+
+/// Walks `path` from `root`, creating tables as needed. When a segment names
+/// an array of tables, the walk continues into its last element.
+fn table_at<'t>(root: &'t mut Table, path: &[String]) -> Result<&'t mut Table, String> {
+    let mut table = root;
+    for key in path {
+        let entry = table
+            .entry(key.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+        table = match entry {
+            Value::Table(t) => t,
+            Value::Array(items) => match items.last_mut() {
+                Some(Value::Table(t)) => t,
+                _ => return Err(format!("`{}` is not a table", key)),
+            },
+            _ => return Err(format!("`{}` is not a table", key)),
+        };
+    }
+    Ok(table)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn error(&self, msg: &str) -> String {
+        error_at(self.line, msg)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_ws();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c)))
+        }
+    }
+
+    /// Skips spaces and tabs on the current line.
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines and comments.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_ws();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') | Some('\r') => {
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_ws();
+        self.skip_comment();
+        self.eat('\r');
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+        }
+    }
+
+    fn key_path(&mut self) -> Result<Vec<String>, String> {
+        let mut path = vec![self.key()?];
+        loop {
+            self.skip_ws();
+            if !self.eat('.') {
+                break;
+            }
+            self.skip_ws();
+            path.push(self.key()?);
+        }
+        Ok(path)
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                    self.bump();
+                }
+                if start == self.pos {
+                    return Err(self.error("expected a key"));
+                }
+                Ok(self.chars[start..self.pos].iter().collect())
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(_) => self.scalar(),
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.bump();
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('e') => out.push('\x1b'),
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.bump()).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("invalid unicode escape"))?;
+                        out.push(c);
+                    }
+                    _ => return Err(self.error("invalid escape sequence")),
+                },
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.bump();
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => return Ok(out),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank();
+            if !self.eat(',') {
+                self.skip_blank();
+                if self.eat(']') {
+                    return Ok(Value::Array(items));
+                }
+                return Err(self.error("expected `,` or `]` in array"));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, String> {
+        self.bump();
+        let mut table = Table::new();
+        self.skip_ws();
+        if self.eat('}') {
+            return Ok(Value::Table(table));
+        }
+        loop {
+            self.skip_ws();
+            let path = self.key_path()?;
+            self.expect('=')?;
+            self.skip_ws();
+            let value = self.value()?;
+            let (last, parents) = path.split_last().ok_or_else(|| self.error("expected a key"))?;
+            let target = table_at(&mut table, parents).map_err(|e| self.error(&e))?;
+            target.insert(last.clone(), value);
+            self.skip_ws();
+            if self.eat('}') {
+                return Ok(Value::Table(table));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if !matches!(c, ',' | ']' | '}' | '#' | '\n' | '\r' | ' ' | '\t')) {
+            self.bump();
+        }
+        let raw: String = self.chars[start..self.pos].iter().collect();
+        match raw.as_str() {
+            "true" => return Ok(Value::Boolean(true)),
+            "false" => return Ok(Value::Boolean(false)),
+            _ => {}
+        }
+        let digits = raw.replace('_', "");
+        if let Ok(i) = digits.parse::<i64>() {
+            return Ok(Value::Integer(i));
+        }
+        if let Ok(f) = digits.parse::<f64>() {
+            return Ok(Value::Float(f));
+        }
+        Err(self.error(&format!("invalid value `{}`", raw)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(value: &Value) -> &Table {
+        match value {
+            Value::Table(t) => t,
+            other => panic!("expected a table, got {}", other.type_name()),
+        }
+    }
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn tables_and_arrays_of_tables() {
+        let root = parse(
+            "top = 1\n\
+             [colors]\n\
+             title = \"blue\"\n\
+             [colors.bar]\n\
+             full = \"green\"\n\
+             [[block]]\n\
+             name = \"a\"\n\
+             [block.style]\n\
+             bold = true\n\
+             [[block]]\n\
+             name = \"b\"\n\
+             [block.style]\n\
+             bold = false\n",
+        )
+        .unwrap();
+        assert_eq!(root["top"], Value::Integer(1));
+        let colors = table(&root["colors"]);
+        assert_eq!(colors["title"], string("blue"));
+        assert_eq!(table(&colors["bar"])["full"], string("green"));
+
+        let Value::Array(blocks) = &root["block"] else { panic!("expected an array") };
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(table(&blocks[0])["name"], string("a"));
+        assert_eq!(table(&table(&blocks[0])["style"])["bold"], Value::Boolean(true));
+        assert_eq!(table(&blocks[1])["name"], string("b"));
+        assert_eq!(table(&table(&blocks[1])["style"])["bold"], Value::Boolean(false));
+    }
+
+    #[test]
+    fn dotted_keys() {
+        let root = parse("a.b.c = 1\n\"quoted.key\" . 'x' = 2\n[t]\nu.v = 3\n").unwrap();
+        assert_eq!(table(&table(&root["a"])["b"])["c"], Value::Integer(1));
+        assert_eq!(table(&root["quoted.key"])["x"], Value::Integer(2));
+        assert_eq!(table(&table(&root["t"])["u"])["v"], Value::Integer(3));
+    }
+
+    #[test]
+    fn strings_and_escapes() {
+        let root = parse(
+            r#"basic = "tab\tquote\" slash\\ \u00e9 \e[1m"
+literal = 'C:\path\n "as is"'
+empty = ""
+"#,
+        )
+        .unwrap();
+        assert_eq!(root["basic"], string("tab\tquote\" slash\\ \u{e9} \x1b[1m"));
+        assert_eq!(root["literal"], string(r#"C:\path\n "as is""#));
+        assert_eq!(root["empty"], string(""));
+        assert!(parse(r#"bad = "\q""#).is_err());
+        assert!(parse("open = \"no end\n").is_err());
+    }
+
+    #[test]
+    fn scalars_arrays_and_inline_tables() {
+        let root = parse(
+            "n = -1_000\nf = 2.5\nb = false\n\
+             list = [\n  \"a\", # comment\n  'b',\n]\n\
+             nested = [[1, 2], []]\n\
+             inline = { x = 1, y.z = \"deep\", list = [true] }\n\
+             none = {}\n",
+        )
+        .unwrap();
+        assert_eq!(root["n"], Value::Integer(-1000));
+        assert_eq!(root["f"], Value::Float(2.5));
+        assert_eq!(root["b"], Value::Boolean(false));
+        assert_eq!(root["list"], Value::Array(vec![string("a"), string("b")]));
+        assert_eq!(
+            root["nested"],
+            Value::Array(vec![Value::Array(vec![Value::Integer(1), Value::Integer(2)]), Value::Array(vec![])])
+        );
+        let inline = table(&root["inline"]);
+        assert_eq!(inline["x"], Value::Integer(1));
+        assert_eq!(table(&inline["y"])["z"], string("deep"));
+        assert_eq!(inline["list"], Value::Array(vec![Value::Boolean(true)]));
+        assert_eq!(root["none"], Value::Table(Table::new()));
+    }
+
+    #[test]
+    fn errors_give_the_line() {
+        assert_eq!(parse("a = 1\n\nb = \n").unwrap_err(), "line 3: invalid value ``");
+        assert_eq!(parse("a = 1\nb = nope\n").unwrap_err(), "line 2: invalid value `nope`");
+        assert_eq!(parse("a = 1\na = 2\n").unwrap_err(), "line 2: duplicate key `a`");
+        assert_eq!(parse("a = 1 2\n").unwrap_err(), "line 1: unexpected `2`");
+        assert_eq!(parse("x = 1\n[x]\n").unwrap_err(), "line 2: `x` is not a table");
+        assert_eq!(
+            parse("[colors]\na = 1\n[other]\n[colors]\nb = 2\n").unwrap_err(),
+            "line 4: duplicate table `[colors]`"
+        );
+        assert_eq!(
+            parse("[[block]]\n[block.style]\n[block.style]\n").unwrap_err(),
+            "line 3: duplicate table `[block.style]`"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Byte-size and percentage formatting shared by everything rendered in
+//! the output, and parsing of sizes written in the config.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Units {
+    /// Powers of 1024: KiB, MiB, GiB, ...
+    #[default]
+    Binary,
+    /// Powers of 1000: kB, MB, GB, ... as printed on disk packaging.
+    Decimal,
+}
+
+impl Units {
+    pub fn parse(name: &str) -> Result<Units, String> {
+        match name {
+            "binary" => Ok(Units::Binary),
+            "decimal" => Ok(Units::Decimal),
+            _ => Err(format!("unknown units `{}` (expected binary or decimal)", name)),
+        }
+    }
+
+    fn base(self) -> f64 {
+        match self {
+            Units::Binary => 1024.0,
+            Units::Decimal => 1000.0,
+        }
+    }
+
+    fn suffixes(self) -> &'static [&'static str] {
+        match self {
+            Units::Binary => &["B", "KiB", "MiB", "GiB", "TiB", "PiB"],
+            Units::Decimal => &["B", "kB", "MB", "GB", "TB", "PB"],
+        }
+    }
+
+    /// The largest unit that keeps `bytes` at or above 1 once rounded to
+    /// `precision` decimals, so 1023.96 MiB shows as `1.0 GiB`, not
+    /// `1024.0 MiB`.
+    fn exponent(self, bytes: u64, precision: usize) -> usize {
+        let scale = 10f64.powi(precision as i32);
+        let mut exp = 0;
+        let mut value = bytes as f64;
+        while (value * scale).round() / scale >= self.base() && exp + 1 < self.suffixes().len() {
+            value /= self.base();
+            exp += 1;
+        }
+        exp
+    }
+
+    fn format_in(self, bytes: u64, exp: usize, precision: usize) -> String {
+        let suffix = self.suffixes()[exp];
+        if exp == 0 {
+            return format!("{} {}", bytes, suffix);
+        }
+        let value = bytes as f64 / self.base().powi(exp as i32);
+        format!("{:.*} {}", precision, value, suffix)
+    }
+}
+
+/// A unit such as `MiB` that sizes are always shown in, instead of the one
+/// picked from their magnitude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unit {
+    units: Units,
+    exp: usize,
+}
+
+impl Unit {
+    /// Parses a suffix as rfetch prints it: `B`, `KiB` to `PiB`, or `kB`
+    /// to `PB`.
+    pub fn parse(name: &str) -> Result<Unit, String> {
+        [Units::Binary, Units::Decimal]
+            .into_iter()
+            .find_map(|units| Some(Unit { units, exp: units.suffixes().iter().position(|s| *s == name)? }))
+            .ok_or_else(|| format!("unknown unit `{}` (expected e.g. MiB or GB)", name))
+    }
+}
+
+/// `percent` rounded to `precision` decimals, as in `94.2%`.
+pub fn format_percent(percent: f64, precision: usize) -> String {
+    format!("{:.*}%", precision, percent)
+}
+
+/// Formats a used/total pair in one shared unit picked from the total, so
+/// `512.0 MiB / 2.0 GiB` style mixes never happen. Values are rounded to
+/// `precision` decimals; plain bytes never have any.
+pub fn format_usage(used: u64, total: u64, units: Units, precision: usize) -> String {
+    let exp = units.exponent(total, precision);
+    format!("{} / {}", units.format_in(used, exp, precision), units.format_in(total, exp, precision))
+}
+
+/// Like `format_usage`, but always in `unit`.
+pub fn format_usage_in(used: u64, total: u64, unit: Unit, precision: usize) -> String {
+    let Unit { units, exp } = unit;
+    format!("{} / {}", units.format_in(used, exp, precision), units.format_in(total, exp, precision))
+}
+
+/// Formats a single size, in the largest unit that keeps it at or above 1.
+pub fn format_size(bytes: u64, units: Units, precision: usize) -> String {
+    units.format_in(bytes, units.exponent(bytes, precision), precision)
+}
+
+/// Parses a size like `512MiB`, `1.5 GB` or `100M`. `KiB`-style suffixes
+/// are powers of 1024 and `kB`-style ones powers of 1000; a bare letter
+/// (`G`) means 1024, as in `df -h`, and no suffix means bytes.
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid size `{}` (expected e.g. 512MiB or 1GB)", text);
+    let trimmed = text.trim();
+    let split = trimmed.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(trimmed.len());
+    let (number, suffix) = trimmed.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+
+    let suffix = suffix.trim().to_ascii_lowercase();
+    let (letter, rest) = match suffix.chars().next() {
+        None => return Ok(number as u64),
+        Some(letter) => (letter, &suffix[letter.len_utf8()..]),
+    };
+    let exp = match letter {
+        'b' if rest.is_empty() => return Ok(number as u64),
+        'k' => 1,
+        'm' => 2,
+        'g' => 3,
+        't' => 4,
+        'p' => 5,
+        _ => return Err(invalid()),
+    };
+    let base: f64 = match rest {
+        "" | "ib" => 1024.0,
+        "b" => 1000.0,
+        _ => return Err(invalid()),
+    };
+    Ok((number * base.powi(exp)) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_shares_one_unit() {
+        let gib = 1024 * 1024 * 1024;
+        assert_eq!(format_usage(gib / 2, 2 * gib, Units::Binary, 1), "0.5 GiB / 2.0 GiB");
+        assert_eq!(format_usage(500, 1000, Units::Binary, 1), "500 B / 1000 B");
+    }
+
+    #[test]
+    fn parse_size_suffixes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("1KiB"), Ok(1024));
+        assert_eq!(parse_size("1kB"), Ok(1000));
+        assert_eq!(parse_size("1.5 GB"), Ok(1_500_000_000));
+        assert_eq!(parse_size("100M"), Ok(100 * 1024 * 1024));
+        assert!(parse_size("12 parsecs").is_err());
+        assert!(parse_size("GiB").is_err());
+    }
+
+    #[test]
+    fn sub_gib_totals_stay_in_mib() {
+        let mib = 1024 * 1024;
+        assert_eq!(format_usage(480 * mib, 512 * mib, Units::Binary, 1), "480.0 MiB / 512.0 MiB");
+    }
+
+    #[test]
+    fn rounding_moves_to_the_next_unit() {
+        let mib = 1024 * 1024;
+        // 1023.96 MiB rounds to 1024.0, so it reads as 1.0 GiB.
+        let total = 1023 * mib + 1000 * 1024;
+        assert_eq!(format_usage(0, total, Units::Binary, 1), "0.0 GiB / 1.0 GiB");
+        assert_eq!(format_usage(0, total, Units::Binary, 2), "0.00 MiB / 1023.98 MiB");
+        assert_eq!(format_usage(0, 1536 * mib, Units::Binary, 0), "0 GiB / 2 GiB");
+    }
+
+    #[test]
+    fn percentages_follow_the_precision() {
+        assert_eq!(format_percent(93.75, 0), "94%");
+        assert_eq!(format_percent(93.75, 2), "93.75%");
+        assert_eq!(format_percent(99.996, 2), "100.00%");
+        assert_eq!(format_percent(0.0, 0), "0%");
+    }
+
+    #[test]
+    fn fixed_unit() {
+        let mib = 1024 * 1024;
+        let unit = Unit::parse("MiB").unwrap();
+        assert_eq!(format_usage_in(2048 * mib, 8192 * mib, unit, 1), "2048.0 MiB / 8192.0 MiB");
+        assert_eq!(format_usage_in(5, 10, Unit::parse("B").unwrap(), 1), "5 B / 10 B");
+        assert_eq!(format_usage_in(500_000_000, 1_000_000_000, Unit::parse("GB").unwrap(), 2), "0.50 GB / 1.00 GB");
+        assert!(Unit::parse("mib").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+//! `--watch`: redraw the fetch in place every few seconds, like `watch(1)`.
+//!
+//! Each frame is built in full and written with one `write_all`, starting
+//! from the cursor's home position instead of a cleared screen, so the
+//! terminal never shows a half-drawn or blank frame.
+
+use std::{
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+use crate::{config::Config, info::SystemInfo};
+
+/// Redraws until stdout goes away. `refresh` updates the volatile fields
+/// between frames; `render` produces each frame's text.
+pub fn watch(
+    interval: Duration,
+    config: &Config,
+    mut info: SystemInfo,
+    refresh: fn(&mut SystemInfo, &Config),
+    render: fn(&SystemInfo, &Config) -> String,
+) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    // Clear once, so the first frame doesn't overlap the shell prompt.
+    stdout.write_all(b"\x1b[2J")?;
+
+    let mut previous_lines = 0;
+    loop {
+        let output = render(&info, config);
+        let lines = output.lines().count();
+
+        let mut frame = String::with_capacity(output.len() + lines * 4 + 8);
+        frame.push_str("\x1b[H");
+        for line in output.lines() {
+            // Erase whatever a longer line from the last frame left behind.
+            frame.push_str(line);
+            frame.push_str("\x1b[K\n");
+        }
+        if lines < previous_lines {
+            frame.push_str("\x1b[J");
+        }
+        previous_lines = lines;
+
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+
+        thread::sleep(interval);
+        refresh(&mut info, config);
+    }
+}
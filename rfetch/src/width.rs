@@ -0,0 +1,208 @@
+//! Terminal display width of strings that may carry ANSI escapes and wide
+//! characters, for anything that pads or aligns output.
+
+use std::io::IsTerminal;
+
+/// Columns and rows of the terminal on stdout, from `TIOCGWINSZ`, then
+/// `$COLUMNS` / `$LINES`. `None` when stdout isn't a terminal.
+pub fn terminal_size() -> Option<(u16, u16)> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+        return Some((size.ws_col, size.ws_row));
+    }
+    let env = |name: &str| std::env::var(name).ok()?.parse::<u16>().ok().filter(|&n| n > 0);
+    Some((env("COLUMNS")?, env("LINES")?))
+}
+
+/// Columns `s` occupies on a terminal. Escape sequences take none, East
+/// Asian wide characters and emoji take two, combining marks and other
+/// zero-width characters take none.
+///
+/// Widths are per character, so multi-codepoint emoji (ZWJ sequences,
+/// flags) count each visible part; that is close enough for labels and
+/// hostnames.
+pub fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            skip_escape(&mut chars);
+            continue;
+        }
+        width += char_width(c);
+    }
+    width
+}
+
+/// Pads `s` with spaces on the right to `width` display columns.
+pub fn pad_right(s: &str, width: usize) -> String {
+    let pad = width.saturating_sub(display_width(s));
+    format!("{}{}", s, " ".repeat(pad))
+}
+
+/// Consumes the rest of an escape sequence whose ESC was already read:
+/// CSI (`ESC [ ... final`), OSC (`ESC ] ... BEL` or `ESC ] ... ESC \\`),
+/// or a two-character escape.
+fn skip_escape(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    match chars.next() {
+        Some('[') => {
+            for c in chars.by_ref() {
+                if ('\x40'..='\x7e').contains(&c) {
+                    break;
+                }
+            }
+        }
+        Some(']') => {
+            while let Some(c) = chars.next() {
+                if c == '\x07' {
+                    break;
+                }
+                if c == '\x1b' && chars.peek() == Some(&'\\') {
+                    chars.next();
+                    break;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn char_width(c: char) -> usize {
+    let cp = c as u32;
+    if cp < 0x20 || (0x7f..0xa0).contains(&cp) || is_zero_width(cp) {
+        0
+    } else if is_wide(cp) {
+        2
+    } else {
+        1
+    }
+}
+
+fn is_zero_width(cp: u32) -> bool {
+    matches!(
+        cp,
+        0x0300..=0x036f   // combining diacritical marks
+            | 0x0483..=0x0489
+            | 0x0591..=0x05bd
+            | 0x0610..=0x061a
+            | 0x064b..=0x065f
+            | 0x1ab0..=0x1aff
+            | 0x1dc0..=0x1dff
+            | 0x200b..=0x200f // zero-width space/joiners, direction marks
+            | 0x2028..=0x202e
+            | 0x2060..=0x2064
+            | 0x20d0..=0x20ff
+            | 0xfe00..=0xfe0f // variation selectors
+            | 0xfe20..=0xfe2f
+            | 0xfeff
+            | 0xe0100..=0xe01ef
+    )
+}
+
+/// East Asian Wide and Fullwidth ranges, plus the emoji blocks that
+/// terminals render double-width.
+fn is_wide(cp: u32) -> bool {
+    matches!(
+        cp,
+        0x1100..=0x115f   // Hangul Jamo
+            | 0x231a..=0x231b
+            | 0x2329..=0x232a
+            | 0x23e9..=0x23ec
+            | 0x23f0
+            | 0x23f3
+            | 0x25fd..=0x25fe
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x267f
+            | 0x2693
+            | 0x26a1
+            | 0x26aa..=0x26ab
+            | 0x26bd..=0x26be
+            | 0x26c4..=0x26c5
+            | 0x26ce
+            | 0x26d4
+            | 0x26ea
+            | 0x26f2..=0x26f3
+            | 0x26f5
+            | 0x26fa
+            | 0x26fd
+            | 0x2705
+            | 0x270a..=0x270b
+            | 0x2728
+            | 0x274c
+            | 0x274e
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27b0
+            | 0x27bf
+            | 0x2b1b..=0x2b1c
+            | 0x2b50
+            | 0x2b55
+            | 0x2e80..=0x303e  // CJK radicals, punctuation
+            | 0x3041..=0x33ff  // kana, CJK compatibility
+            | 0x3400..=0x4dbf  // CJK extension A
+            | 0x4e00..=0x9fff  // CJK unified ideographs
+            | 0xa000..=0xa4cf  // Yi
+            | 0xa960..=0xa97f
+            | 0xac00..=0xd7a3  // Hangul syllables
+            | 0xf900..=0xfaff  // CJK compatibility ideographs
+            | 0xfe10..=0xfe19
+            | 0xfe30..=0xfe6f
+            | 0xff00..=0xff60  // fullwidth forms
+            | 0xffe0..=0xffe6
+            | 0x16fe0..=0x16fe4
+            | 0x17000..=0x18cff
+            | 0x1b000..=0x1b2ff
+            | 0x1f004
+            | 0x1f0cf
+            | 0x1f18e
+            | 0x1f191..=0x1f19a
+            | 0x1f200..=0x1f251
+            | 0x1f300..=0x1f64f // pictographs, emoticons
+            | 0x1f680..=0x1f6ff // transport and map symbols
+            | 0x1f7e0..=0x1f7eb
+            | 0x1f90c..=0x1f9ff // supplemental symbols and pictographs
+            | 0x1fa70..=0x1faff
+            | 0x20000..=0x2fffd
+            | 0x30000..=0x3fffd
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_take_no_columns() {
+        assert_eq!(display_width("\x1b[1;38;2;255;128;0mbold\x1b[0m"), 4);
+        assert_eq!(display_width("\x1b]8;;https://example.com\x07link\x1b]8;;\x07"), 4);
+        assert_eq!(display_width("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\"), 4);
+        assert_eq!(display_width("a\x1b7b"), 2);
+        assert_eq!(display_width("\x1b[31"), 0);
+    }
+
+    #[test]
+    fn wide_and_zero_width_characters() {
+        assert_eq!(display_width("内核"), 4);
+        assert_eq!(display_width("ＲＸ"), 4);
+        assert_eq!(display_width("ｶﾀｶﾅ"), 4);
+        assert_eq!(display_width("한글"), 4);
+        assert_eq!(display_width("🐧 Linux"), 8);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("a\u{200b}b"), 2);
+        assert_eq!(display_width("☀\u{fe0f}"), 1);
+        assert_eq!(display_width("tab\there"), 7);
+    }
+
+    #[test]
+    fn padding() {
+        assert_eq!(pad_right("OS", 6), "OS    ");
+        assert_eq!(pad_right("内核", 6), "内核  ");
+        assert_eq!(pad_right("\x1b[1mOS\x1b[0m", 4), "\x1b[1mOS\x1b[0m  ");
+        assert_eq!(pad_right("Terminal", 4), "Terminal");
+    }
+}